@@ -0,0 +1,546 @@
+//! Сравнение двух зданий
+//!
+//! Этажи сопоставляются по номеру, элементы этажей - по геометрии (точки p, p1/p2,
+//! координаты узлов полилиний и нагрузок, контуры плит), так что сдвиг номеров
+//! элементов не дает ложных изменений. Элементы без геометрии
+//! и оставшиеся без пары сопоставляются по номеру. Изменения показываются по полям,
+//! кроме ссылок на номера других элементов и счетчиков (poly_from, node_num, op_num...).
+use crate::sig::building::Building;
+use crate::sig::storey::Storey;
+use crate::text::to_value;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+/// Изменение поля: путь (sec.Rectangle.b), старое и новое значение
+#[derive(Debug)]
+pub struct FieldChange {
+    pub path: String,
+    pub old: Value,
+    pub new: Value,
+}
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} → {}", &self.path, &self.old, &self.new)
+    }
+}
+
+/// Изменение элемента. Номера элементов - в старом и новом файле
+#[derive(Debug)]
+pub enum Change {
+    Added(usize),
+    Removed(usize),
+    Modified(usize, usize, Vec<FieldChange>),
+}
+
+/// Изменение в здании
+#[derive(Debug)]
+pub struct ElementChange {
+    pub place: String, //"storey 3", "slits"
+    pub kind: String,  //Тип элемента: wall, column, head...
+    pub change: Change,
+}
+impl fmt::Display for ElementChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.change {
+            Change::Added(_) if self.kind == "storey" => write!(f, "{} added", &self.place),
+            Change::Removed(_) if self.kind == "storey" => write!(f, "{} removed", &self.place),
+            Change::Added(n) => write!(f, "{} {} {} added", &self.place, &self.kind, n),
+            Change::Removed(n) => write!(f, "{} {} {} removed", &self.place, &self.kind, n),
+            Change::Modified(old, new, fields) => {
+                if old == new {
+                    write!(f, "{} {} {}", &self.place, &self.kind, old)?;
+                } else {
+                    write!(f, "{} {} {} (was {})", &self.place, &self.kind, new, old)?;
+                }
+                for field in fields {
+                    write!(f, "\n    {}", field)?;
+                }
+                write!(f, "")
+            }
+        }
+    }
+}
+
+/// Результат сравнения
+#[derive(Debug, Default)]
+pub struct BuildingDiff {
+    pub elements: Vec<ElementChange>,
+    pub signatures: Vec<FieldChange>, //Изменившиеся сигнатуры кроме rab.e и slits.slt
+}
+impl BuildingDiff {
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty() && self.signatures.is_empty()
+    }
+}
+impl fmt::Display for BuildingDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for v in &self.elements {
+            writeln!(f, "{}", v)?;
+        }
+        for v in &self.signatures {
+            writeln!(
+                f,
+                "signature {}: {} → {}",
+                &v.path,
+                summary(&v.old),
+                summary(&v.new)
+            )?;
+        }
+        write!(f, "")
+    }
+}
+/// Краткое описание сигнатуры для отчета
+fn summary(value: &Value) -> String {
+    match value {
+        Value::Null => "none".to_string(),
+        Value::Object(map) => match map.get("source") {
+            Some(Value::String(hex)) => format!("{} bytes", hex.len() / 2),
+            _ => "present".to_string(),
+        },
+        _ => value.to_string(),
+    }
+}
+
+/// Сравнение зданий old и new
+pub fn diff(old: &Building, new: &Building) -> BuildingDiff {
+    let mut out = BuildingDiff::default();
+    diff_storeys(&mut out, &old.storeys(), &new.storeys());
+    let old_value = to_value(old);
+    let new_value = to_value(new);
+    let old_slits = &old_value["slits_slt"]["slits"];
+    let new_slits = &new_value["slits_slt"]["slits"];
+    let ctx = (&Value::Null, &Value::Null);
+    diff_group(&mut out, "slits", "slit", (old_slits, new_slits), ctx);
+    if let (Value::Object(old_map), Value::Object(new_map)) = (&old_value, &new_value) {
+        for (key, old_sig) in old_map.iter() {
+            let new_sig = new_map.get(key).unwrap_or(&Value::Null);
+            if key == "rab_e" || old_sig == new_sig {
+                continue;
+            }
+            if key == "slits_slt" && !old_sig.is_null() && !new_sig.is_null() {
+                let mut fields = vec![];
+                diff_value(
+                    &mut fields,
+                    "",
+                    &without(old_sig, "slits"),
+                    &without(new_sig, "slits"),
+                );
+                if !fields.is_empty() {
+                    out.elements.push(ElementChange {
+                        place: "slits".to_string(),
+                        kind: "head".to_string(),
+                        change: Change::Modified(0, 0, fields),
+                    });
+                }
+                continue;
+            }
+            out.signatures.push(FieldChange {
+                path: key.clone(),
+                old: old_sig.clone(),
+                new: new_sig.clone(),
+            });
+        }
+    }
+    out
+}
+
+/// Поля-ссылки на номера других элементов и счетчики. Меняются при вставке и удалении
+/// элементов, поэтому в сравнение полей не входят
+const REFS: [&str; 26] = [
+    "poly_from",
+    "poly_to",
+    "poly_num",
+    "poly_prev",
+    "poly_next",
+    "node_from",
+    "node_to",
+    "node_num",
+    "node_prev",
+    "node_next",
+    "sig_num",
+    "op_num",
+    "force_from",
+    "force_to",
+    "force_num",
+    "diagram_from",
+    "diagram_to",
+    "diagram_num",
+    "diagram_prev",
+    "diagram_next",
+    "diagram_fwall_from",
+    "diagram_fwall_to",
+    "diagram_fwall_num",
+    "diagram_horizontal_from",
+    "diagram_horizontal_to",
+    "diagram_horizontal_num",
+];
+
+/// Элемент без полей REFS
+fn without_refs(value: &Value) -> Value {
+    let mut value = value.clone();
+    if let Value::Object(map) = &mut value {
+        for key in REFS.iter() {
+            map.remove(*key);
+        }
+    }
+    value
+}
+
+fn without(value: &Value, key: &str) -> Value {
+    let mut value = value.clone();
+    if let Value::Object(map) = &mut value {
+        map.remove(key);
+    }
+    value
+}
+
+fn diff_storeys(out: &mut BuildingDiff, old: &[Storey], new: &[Storey]) {
+    for old_storey in old {
+        let place = format!("storey {}", old_storey.etazh_num);
+        let new_storey = match new.iter().find(|s| s.etazh_num == old_storey.etazh_num) {
+            None => {
+                out.elements.push(ElementChange {
+                    place,
+                    kind: "storey".to_string(),
+                    change: Change::Removed(old_storey.index),
+                });
+                continue;
+            }
+            Some(s) => s,
+        };
+        let old_value = to_value(old_storey.rab_e);
+        let new_value = to_value(new_storey.rab_e);
+        let (old_map, new_map) = match (&old_value, &new_value) {
+            (Value::Object(old_map), Value::Object(new_map)) => (old_map, new_map),
+            _ => continue,
+        };
+        let mut head = vec![];
+        for (key, old_v) in old_map.iter() {
+            let new_v = new_map.get(key).unwrap_or(&Value::Null);
+            match (old_v, new_v) {
+                (Value::Array(_), Value::Array(_)) => {
+                    let ctx = (&old_value, &new_value);
+                    diff_group(out, &place, key, (old_v, new_v), ctx)
+                }
+                _ => diff_value(&mut head, key, old_v, new_v),
+            }
+        }
+        //Счетчики заголовка повторяют количество элементов, изменения видны по элементам
+        head.retain(|c| !c.path.starts_with("head.") || !c.path.ends_with("_num"));
+        if !head.is_empty() {
+            out.elements.push(ElementChange {
+                place,
+                kind: "head".to_string(),
+                change: Change::Modified(old_storey.index, new_storey.index, head),
+            });
+        }
+    }
+    for new_storey in new {
+        if !old.iter().any(|s| s.etazh_num == new_storey.etazh_num) {
+            out.elements.push(ElementChange {
+                place: format!("storey {}", new_storey.etazh_num),
+                kind: "storey".to_string(),
+                change: Change::Added(new_storey.index),
+            });
+        }
+    }
+}
+
+/// Сравнение массивов элементов одного типа. ctx - этажи rab_e (старый, новый) для
+/// геометрии элементов по узлам и полилиниям
+fn diff_group(
+    out: &mut BuildingDiff,
+    place: &str,
+    kind: &str,
+    (old, new): (&Value, &Value),
+    ctx: (&Value, &Value),
+) {
+    let empty = vec![];
+    let old = old.as_array().unwrap_or(&empty);
+    let new = new.as_array().unwrap_or(&empty);
+    let pairs = match_elements(old, new, ctx);
+    let mut changes = vec![];
+    for (old_n, new_n) in pairs.iter() {
+        let change = match (old_n, new_n) {
+            (Some(o), Some(n)) => {
+                let mut fields = vec![];
+                diff_value(
+                    &mut fields,
+                    "",
+                    &without_refs(&old[*o]),
+                    &without_refs(&new[*n]),
+                );
+                //Перенумерация без изменений полей не интересна
+                if fields.is_empty() {
+                    continue;
+                }
+                Change::Modified(*o, *n, fields)
+            }
+            (Some(o), None) => Change::Removed(*o),
+            (None, Some(n)) => Change::Added(*n),
+            (None, None) => continue,
+        };
+        changes.push(change);
+    }
+    for change in changes {
+        out.elements.push(ElementChange {
+            place: place.to_string(),
+            kind: kind.to_string(),
+            change,
+        });
+    }
+}
+
+/// Пары (старый, новый) номеров элементов
+///
+/// Сначала по совпадению геометрии, затем оставшиеся по номеру.
+fn match_elements(
+    old: &[Value],
+    new: &[Value],
+    (old_ctx, new_ctx): (&Value, &Value),
+) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut new_used = vec![false; new.len()];
+    let mut old_pair: Vec<Option<usize>> = vec![None; old.len()];
+    //Номера новых элементов по геометрии, в обратном порядке: pop дает первый
+    let mut by_key: HashMap<String, Vec<usize>> = HashMap::new();
+    for (n, new_v) in new.iter().enumerate().rev() {
+        if let Some(key) = geometry_key(new_v, new_ctx) {
+            by_key.entry(key).or_default().push(n);
+        }
+    }
+    for (o, old_v) in old.iter().enumerate() {
+        let found = geometry_key(old_v, old_ctx)
+            .and_then(|key| by_key.get_mut(&key))
+            .and_then(|vec| vec.pop());
+        if let Some(n) = found {
+            new_used[n] = true;
+            old_pair[o] = Some(n);
+        }
+    }
+    for o in 0..old.len() {
+        if old_pair[o].is_none() && o < new.len() && !new_used[o] {
+            new_used[o] = true;
+            old_pair[o] = Some(o);
+        }
+    }
+    let mut pairs: Vec<(Option<usize>, Option<usize>)> = old_pair
+        .iter()
+        .enumerate()
+        .map(|(o, n)| (Some(o), *n))
+        .collect();
+    for (n, used) in new_used.iter().enumerate() {
+        if !used {
+            pairs.push((None, Some(n)));
+        }
+    }
+    pairs
+}
+
+/// Номера from..from + num по полям элемента
+fn range(value: &Value, from: &str, num: &str) -> Option<Range<usize>> {
+    let from = value.get(from)?.as_u64()? as usize;
+    Some(from..from + value.get(num)?.as_u64()? as usize)
+}
+
+/// Геометрия элемента, округление до мм: точка p, отрезок p1-p2 (без учета направления),
+/// узлы node_from.. или полилинии poly_from.. этажа rab_e
+fn geometry_key(value: &Value, rab_e: &Value) -> Option<String> {
+    let point = |v: &Value| -> Option<String> {
+        Some(format!("{:.3};{:.3}", v["x"].as_f64()?, v["y"].as_f64()?))
+    };
+    if let Some(p) = value.get("p") {
+        return point(p);
+    }
+    if let Some(nodes) = range(value, "node_from", "node_num") {
+        let nodes = rab_e["node"].as_array()?.get(nodes)?;
+        let points: Option<Vec<String>> = nodes.iter().map(|n| point(&n["p"])).collect();
+        return Some(points?.join(",")).filter(|key| !key.is_empty());
+    }
+    if let Some(polys) = range(value, "poly_from", "poly_num") {
+        let polys = rab_e["poly"].as_array()?.get(polys)?;
+        let keys: Option<Vec<String>> = polys.iter().map(|p| geometry_key(p, rab_e)).collect();
+        return Some(keys?.join("/")).filter(|key| !key.is_empty());
+    }
+    let mut p = [point(value.get("p1")?)?, point(value.get("p2")?)?];
+    p.sort();
+    Some(p.join("|"))
+}
+
+/// Изменения полей, вложенные объекты разворачиваются в путь через точку
+fn diff_value(out: &mut Vec<FieldChange>, path: &str, old: &Value, new: &Value) {
+    if old == new {
+        return;
+    }
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            let null = Value::Null;
+            for (key, old_v) in old_map.iter() {
+                diff_value(out, &join(key), old_v, new_map.get(key).unwrap_or(&null));
+            }
+            for (key, new_v) in new_map.iter() {
+                if !old_map.contains_key(key) {
+                    diff_value(out, &join(key), &null, new_v);
+                }
+            }
+        }
+        _ => out.push(FieldChange {
+            path: path.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::rab_e_sig_test::{
+        rab_e_test_sig, rab_e_test_sig_full, read_test_building, read_test_sig,
+    };
+
+    fn walls(walls: &[&str]) -> Vec<u8> {
+        rab_e_test_sig(1, 3.0, &["test_sig/columns/column_box.test"], walls)
+    }
+
+    #[test]
+    fn diff_same() {
+        let old = read_test_building(&[walls(&["test_sig/walls/wall.test"])]);
+        let new = read_test_building(&[walls(&["test_sig/walls/wall.test"])]);
+        assert!(diff(&old, &new).is_empty());
+    }
+    #[test]
+    fn diff_shifted_index() {
+        let old = read_test_building(&[walls(&[
+            "test_sig/walls/wall_opening_1.test",
+            "test_sig/walls/S_wall.test",
+        ])]);
+        let new = read_test_building(&[walls(&["test_sig/walls/S_wall.test"])]);
+        let diff = diff(&old, &new);
+        assert_eq!(diff.elements.len(), 1, "{}", diff);
+        assert_eq!(diff.elements[0].kind, "wall");
+        assert!(matches!(diff.elements[0].change, Change::Removed(0)));
+    }
+    #[test]
+    fn diff_modified_field() {
+        let old = read_test_building(&[
+            walls(&["test_sig/walls/wall.test"]),
+            read_test_sig("test_sig/slits/1slits.test"),
+        ]);
+        let mut value = to_value(&old);
+        value["rab_e"][0]["wall"][0]["b"] = serde_json::json!(25.0);
+        value["slits_slt"]["slits"][0]["d1"] = serde_json::json!(0.5);
+        let new: Building = serde_json::from_value(value).expect("couldn't deserialize");
+        let diff = diff(&old, &new);
+        assert_eq!(diff.elements.len(), 2, "{}", diff);
+        let text = diff.to_string();
+        assert!(text.contains("storey 1 wall 0\n    b: "), "{}", text);
+        assert!(text.contains(" → 25.0"), "{}", text);
+        assert!(
+            text.contains("slits slit 0\n    d1: 0.12 → 0.5"),
+            "{}",
+            text
+        );
+    }
+    #[test]
+    fn diff_storeys_and_signatures() {
+        let old = read_test_building(&[walls(&[]), read_test_sig("test_sig/slits/1slits.test")]);
+        let new = read_test_building(&[walls(&[]), rab_e_test_sig(2, 3.0, &[], &[])]);
+        let diff = diff(&old, &new);
+        let text = diff.to_string();
+        assert!(text.contains("storey 2 added"), "{}", text);
+        assert!(
+            text.contains("signature slits_slt: present → none"),
+            "{}",
+            text
+        );
+    }
+    #[test]
+    fn diff_match_slabs() {
+        let node = |x: f32, y: f32| serde_json::json!({ "p": { "x": x, "y": y } });
+        let poly = |from: u16| serde_json::json!({ "node_from": from, "node_num": 3 });
+        let slab =
+            |from: u16, b: f32| serde_json::json!({ "poly_from": from, "poly_num": 1, "b": b });
+        let nodes = vec![
+            node(0.0, 0.0),
+            node(1.0, 0.0),
+            node(0.0, 1.0),
+            node(5.0, 5.0),
+            node(6.0, 5.0),
+            node(5.0, 6.0),
+        ];
+        let old_ctx = serde_json::json!({ "node": nodes, "poly": [poly(0), poly(3)] });
+        //Полилинии и плиты в обратном порядке, вторая плита толще
+        let new_ctx = serde_json::json!({ "node": nodes, "poly": [poly(3), poly(0)] });
+        let old = [slab(0, 20.0), slab(1, 20.0)];
+        let new = [slab(0, 20.0), slab(1, 25.0)];
+        let ctx = (&old_ctx, &new_ctx);
+        assert_eq!(
+            match_elements(&old, &new, ctx),
+            vec![(Some(0), Some(1)), (Some(1), Some(0))]
+        );
+        let old_polys = old_ctx["poly"].as_array().expect("no polys");
+        let new_polys = new_ctx["poly"].as_array().expect("no polys");
+        assert_eq!(
+            match_elements(old_polys, new_polys, ctx),
+            vec![(Some(0), Some(1)), (Some(1), Some(0))]
+        );
+    }
+    #[test]
+    fn diff_inserted_slab() {
+        let old = read_test_building(&[rab_e_test_sig_full(1, 3.0)]);
+        let mut value = to_value(&old);
+        let rab_e = &mut value["rab_e"][0];
+        //Сдвиг ссылок на узлы и полилинии на вставленные в начало
+        let shift = |elements: &mut Value, keys: &[&str], by: u64| {
+            for element in elements.as_array_mut().expect("no elements") {
+                for key in keys {
+                    let n = element[*key].as_u64().expect("no ref");
+                    element[*key] = serde_json::json!(n + by);
+                }
+            }
+        };
+        shift(&mut rab_e["poly"], &["node_from", "node_to"], 3);
+        shift(&mut rab_e["load"], &["node_from", "node_to"], 3);
+        shift(&mut rab_e["slab"], &["poly_from", "poly_to"], 1);
+        shift(&mut rab_e["f_slab"], &["poly_from", "poly_to"], 1);
+        let nodes = rab_e["node"].as_array_mut().expect("no nodes");
+        for (n, (x, y)) in [(100.0, 100.0), (101.0, 100.0), (100.0, 101.0)]
+            .iter()
+            .enumerate()
+        {
+            let mut node = nodes[0].clone();
+            node["p"] = serde_json::json!({ "x": x, "y": y });
+            nodes.insert(n, node);
+        }
+        let polys = rab_e["poly"].as_array_mut().expect("no polys");
+        let mut poly = polys[0].clone();
+        poly["node_from"] = serde_json::json!(0);
+        poly["node_to"] = serde_json::json!(2);
+        poly["node_num"] = serde_json::json!(3);
+        polys.insert(0, poly);
+        let slabs = rab_e["slab"].as_array_mut().expect("no slabs");
+        let mut slab = slabs[0].clone();
+        slab["poly_from"] = serde_json::json!(0);
+        slab["poly_to"] = serde_json::json!(0);
+        slab["poly_num"] = serde_json::json!(1);
+        slabs.insert(0, slab);
+        let new: Building = serde_json::from_value(value).expect("couldn't deserialize");
+        let diff = diff(&old, &new);
+        for change in diff.elements.iter() {
+            assert!(matches!(change.change, Change::Added(_)), "{}", diff);
+        }
+        let slabs: Vec<&ElementChange> =
+            diff.elements.iter().filter(|c| c.kind == "slab").collect();
+        assert_eq!(slabs.len(), 1, "{}", diff);
+        assert!(matches!(slabs[0].change, Change::Added(0)));
+    }
+}
@@ -0,0 +1,211 @@
+//! Геометрия в плане
+//!
+//! Расстояния и пересечения точек, отрезков и многоугольников для геометрии элементов
+//! (Footprint). Координаты в м.
+use crate::sig::element::{Footprint, Point};
+
+/// Точность сравнения координат, м
+pub const EPS: f32 = 1e-4;
+
+pub fn point(x: f32, y: f32) -> Point {
+    Point { x, y }
+}
+fn sub(a: &Point, b: &Point) -> Point {
+    point(a.x - b.x, a.y - b.y)
+}
+pub fn dot(v: &Point, w: &Point) -> f32 {
+    v.x * w.x + v.y * w.y
+}
+/// Псевдоскалярное произведение, больше 0 - поворот против часовой стрелки
+pub fn cross(v: &Point, w: &Point) -> f32 {
+    v.x * w.y - v.y * w.x
+}
+pub fn length(v: &Point) -> f32 {
+    dot(v, v).sqrt()
+}
+pub fn distance(a: &Point, b: &Point) -> f32 {
+    length(&sub(a, b))
+}
+
+/// Расстояние от точки до отрезка
+pub fn distance_to_segment(p: &Point, a: &Point, b: &Point) -> f32 {
+    let ab = sub(b, a);
+    let len_2 = dot(&ab, &ab);
+    if len_2 == 0.0 {
+        return distance(p, a);
+    }
+    let t = (dot(&sub(p, a), &ab) / len_2).clamp(0.0, 1.0);
+    distance(p, &point(a.x + ab.x * t, a.y + ab.y * t))
+}
+
+/// Пересечение отрезков, включая касание и наложение
+pub fn segments_intersect(a1: &Point, a2: &Point, b1: &Point, b2: &Point) -> bool {
+    let d1 = cross(&sub(a2, a1), &sub(b1, a1));
+    let d2 = cross(&sub(a2, a1), &sub(b2, a1));
+    let d3 = cross(&sub(b2, b1), &sub(a1, b1));
+    let d4 = cross(&sub(b2, b1), &sub(a2, b1));
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+    distance_to_segment(b1, a1, a2) < EPS
+        || distance_to_segment(b2, a1, a2) < EPS
+        || distance_to_segment(a1, b1, b2) < EPS
+        || distance_to_segment(a2, b1, b2) < EPS
+}
+
+/// Стороны многоугольника
+pub fn edges(polygon: &[Point]) -> impl Iterator<Item = (&Point, &Point)> {
+    let n = polygon.len();
+    (0..n).map(move |i| (&polygon[i], &polygon[(i + 1) % n]))
+}
+
+/// Точка внутри многоугольника или на его границе
+pub fn point_in_polygon(p: &Point, polygon: &[Point]) -> bool {
+    let mut inside = false;
+    for (a, b) in edges(polygon) {
+        if distance_to_segment(p, a, b) < EPS {
+            return true;
+        }
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Отрезок пересекает многоугольник или лежит внутри него
+pub fn segment_in_polygon(a: &Point, b: &Point, polygon: &[Point]) -> bool {
+    point_in_polygon(a, polygon)
+        || point_in_polygon(b, polygon)
+        || edges(polygon).any(|(p1, p2)| segments_intersect(a, b, p1, p2))
+}
+
+/// Многоугольники пересекаются (общая точка, включая вложение)
+pub fn polygons_intersect(first: &[Point], second: &[Point]) -> bool {
+    first.iter().any(|p| point_in_polygon(p, second))
+        || second.iter().any(|p| point_in_polygon(p, first))
+        || edges(first).any(|(a, b)| edges(second).any(|(c, d)| segments_intersect(a, b, c, d)))
+}
+
+impl Footprint {
+    /// Расстояние от точки до геометрии, 0 внутри контура
+    pub fn distance(&self, p: &Point) -> f32 {
+        match self {
+            Footprint::Point(a) => distance(p, a),
+            Footprint::Line(a, b) => distance_to_segment(p, a, b),
+            Footprint::Polygon(polygon) if point_in_polygon(p, polygon) => 0.0,
+            Footprint::Polygon(polygon) => edges(polygon)
+                .map(|(a, b)| distance_to_segment(p, a, b))
+                .fold(f32::INFINITY, f32::min),
+            Footprint::None => f32::INFINITY,
+        }
+    }
+    /// Геометрия имеет общую точку с многоугольником
+    pub fn intersects_polygon(&self, polygon: &[Point]) -> bool {
+        match self {
+            Footprint::Point(p) => point_in_polygon(p, polygon),
+            Footprint::Line(a, b) => segment_in_polygon(a, b, polygon),
+            Footprint::Polygon(own) => polygons_intersect(own, polygon),
+            Footprint::None => false,
+        }
+    }
+    /// Геометрия имеет общую точку с отрезком
+    pub fn crosses_segment(&self, a: &Point, b: &Point) -> bool {
+        match self {
+            Footprint::Point(p) => distance_to_segment(p, a, b) < EPS,
+            Footprint::Line(p1, p2) => segments_intersect(p1, p2, a, b),
+            Footprint::Polygon(polygon) => segment_in_polygon(a, b, polygon),
+            Footprint::None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square() -> Vec<Point> {
+        vec![
+            point(0.0, 0.0),
+            point(2.0, 0.0),
+            point(2.0, 2.0),
+            point(0.0, 2.0),
+        ]
+    }
+
+    #[test]
+    fn segment_distance() {
+        let (a, b) = (point(0.0, 0.0), point(2.0, 0.0));
+        assert_eq!(distance_to_segment(&point(1.0, 1.0), &a, &b), 1.0);
+        assert_eq!(distance_to_segment(&point(3.0, 0.0), &a, &b), 1.0);
+        assert_eq!(distance_to_segment(&point(1.0, 0.0), &a, &a), 1.0);
+    }
+    #[test]
+    fn segments() {
+        let (a, b) = (point(0.0, 0.0), point(2.0, 2.0));
+        assert!(segments_intersect(
+            &a,
+            &b,
+            &point(0.0, 2.0),
+            &point(2.0, 0.0)
+        ));
+        assert!(segments_intersect(
+            &a,
+            &b,
+            &point(2.0, 2.0),
+            &point(3.0, 0.0)
+        ));
+        assert!(segments_intersect(
+            &a,
+            &b,
+            &point(1.0, 1.0),
+            &point(3.0, 3.0)
+        ));
+        assert!(!segments_intersect(
+            &a,
+            &b,
+            &point(3.0, 3.0),
+            &point(4.0, 4.0)
+        ));
+        assert!(!segments_intersect(
+            &a,
+            &b,
+            &point(1.0, 0.0),
+            &point(2.0, 0.0)
+        ));
+    }
+    #[test]
+    fn polygon() {
+        let square = square();
+        assert!(point_in_polygon(&point(1.0, 1.0), &square));
+        assert!(point_in_polygon(&point(2.0, 1.0), &square));
+        assert!(!point_in_polygon(&point(3.0, 1.0), &square));
+        assert!(segment_in_polygon(
+            &point(-1.0, 1.0),
+            &point(3.0, 1.0),
+            &square
+        ));
+        assert!(!segment_in_polygon(
+            &point(-1.0, 3.0),
+            &point(3.0, 3.0),
+            &square
+        ));
+        let inner = vec![point(0.5, 0.5), point(1.0, 0.5), point(1.0, 1.0)];
+        assert!(polygons_intersect(&square, &inner));
+        let far = vec![point(5.0, 5.0), point(6.0, 5.0), point(6.0, 6.0)];
+        assert!(!polygons_intersect(&square, &far));
+    }
+    #[test]
+    fn footprint() {
+        let slab = Footprint::Polygon(square());
+        assert_eq!(slab.distance(&point(1.0, 1.0)), 0.0);
+        assert_eq!(slab.distance(&point(3.0, 1.0)), 1.0);
+        assert!(slab.crosses_segment(&point(1.0, -1.0), &point(1.0, 3.0)));
+        let wall = Footprint::Line(point(0.0, 0.0), point(0.0, 3.0));
+        assert!(wall.crosses_segment(&point(-1.0, 1.0), &point(1.0, 1.0)));
+        assert!(!wall.intersects_polygon(&[point(1.0, 0.0), point(2.0, 0.0), point(2.0, 1.0)]));
+        assert_eq!(Footprint::None.distance(&point(0.0, 0.0)), f32::INFINITY);
+    }
+}
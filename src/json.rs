@@ -0,0 +1,466 @@
+//! JSON без потери неконечных f32
+//!
+//! serde_json пишет NaN и ±inf как null, и такой файл потом не читается в поле f32. Здесь
+//! неконечные f32 записываются строкой с битами числа ("f32:7fc00000"), при чтении строка
+//! в поле f32 возвращается тем же числом. Остальные значения - как у serde_json.
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::ser::{self, Serialize, Serializer};
+use serde_json::{Error, Map, Value};
+use std::io::{Read, Write};
+
+const F32_PREFIX: &str = "f32:";
+
+/// Неконечное f32 в строку с битами числа
+fn f32_to_str(v: f32) -> String {
+    format!("{}{:08x}", F32_PREFIX, v.to_bits())
+}
+/// Строка с битами числа в f32
+fn f32_from_str(str: &str) -> Option<f32> {
+    let bits = str.strip_prefix(F32_PREFIX)?;
+    if bits.len() != 8 {
+        return None;
+    }
+    u32::from_str_radix(bits, 16).ok().map(f32::from_bits)
+}
+
+/// Запись в JSON с отступами
+pub fn to_writer_pretty<W: Write, T: Serialize + ?Sized>(
+    writer: W,
+    value: &T,
+) -> Result<(), Error> {
+    value.serialize(Ser(&mut serde_json::Serializer::pretty(writer)))
+}
+/// Запись в строку JSON
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    let mut out = vec![];
+    value.serialize(Ser(&mut serde_json::Serializer::new(&mut out)))?;
+    String::from_utf8(out).map_err(de::Error::custom)
+}
+/// Чтение из JSON
+pub fn from_reader<R: Read, T: DeserializeOwned>(reader: R) -> Result<T, Error> {
+    from_value(serde_json::from_reader(reader)?)
+}
+/// Чтение из JSON-дерева
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+    T::deserialize(De(value))
+}
+
+/// Сериализатор-обертка: неконечные f32 строкой, остальное - во внутренний сериализатор
+struct Ser<S>(S);
+
+/// Значение, вложенные значения которого пишутся через Ser
+struct Wrap<'a, T: ?Sized>(&'a T);
+impl<T: Serialize + ?Sized> Serialize for Wrap<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(Ser(serializer))
+    }
+}
+
+macro_rules! forward_ser {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(fn $method(self, $($arg: $ty),*) -> Result<Self::Ok, Self::Error> {
+            self.0.$method($($arg),*)
+        })*
+    };
+}
+
+impl<S: Serializer> Serializer for Ser<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Ser<S::SerializeSeq>;
+    type SerializeTuple = Ser<S::SerializeTuple>;
+    type SerializeTupleStruct = Ser<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Ser<S::SerializeTupleVariant>;
+    type SerializeMap = Ser<S::SerializeMap>;
+    type SerializeStruct = Ser<S::SerializeStruct>;
+    type SerializeStructVariant = Ser<S::SerializeStructVariant>;
+
+    forward_ser! {
+        serialize_bool(v: bool);
+        serialize_i8(v: i8);
+        serialize_i16(v: i16);
+        serialize_i32(v: i32);
+        serialize_i64(v: i64);
+        serialize_i128(v: i128);
+        serialize_u8(v: u8);
+        serialize_u16(v: u16);
+        serialize_u32(v: u32);
+        serialize_u64(v: u64);
+        serialize_u128(v: u128);
+        serialize_f64(v: f64);
+        serialize_char(v: char);
+        serialize_str(v: &str);
+        serialize_bytes(v: &[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(name: &'static str);
+        serialize_unit_variant(name: &'static str, index: u32, variant: &'static str);
+    }
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        if v.is_finite() {
+            self.0.serialize_f32(v)
+        } else {
+            self.0.serialize_str(&f32_to_str(v))
+        }
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, v: &T) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_some(&Wrap(v))
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        v: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_newtype_struct(name, &Wrap(v))
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        v: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0
+            .serialize_newtype_variant(name, index, variant, &Wrap(v))
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        self.0.serialize_seq(len).map(Ser)
+    }
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.0.serialize_tuple(len).map(Ser)
+    }
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.0.serialize_tuple_struct(name, len).map(Ser)
+    }
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.0
+            .serialize_tuple_variant(name, index, variant, len)
+            .map(Ser)
+    }
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        self.0.serialize_map(len).map(Ser)
+    }
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.0.serialize_struct(name, len).map(Ser)
+    }
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.0
+            .serialize_struct_variant(name, index, variant, len)
+            .map(Ser)
+    }
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+impl<S: ser::SerializeSeq> ser::SerializeSeq for Ser<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), Self::Error> {
+        self.0.serialize_element(&Wrap(v))
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+impl<S: ser::SerializeTuple> ser::SerializeTuple for Ser<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), Self::Error> {
+        self.0.serialize_element(&Wrap(v))
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+impl<S: ser::SerializeTupleStruct> ser::SerializeTupleStruct for Ser<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), Self::Error> {
+        self.0.serialize_field(&Wrap(v))
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+impl<S: ser::SerializeTupleVariant> ser::SerializeTupleVariant for Ser<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), Self::Error> {
+        self.0.serialize_field(&Wrap(v))
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+impl<S: ser::SerializeMap> ser::SerializeMap for Ser<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, k: &T) -> Result<(), Self::Error> {
+        self.0.serialize_key(&Wrap(k))
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, v: &T) -> Result<(), Self::Error> {
+        self.0.serialize_value(&Wrap(v))
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+impl<S: ser::SerializeStruct> ser::SerializeStruct for Ser<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        v: &T,
+    ) -> Result<(), Self::Error> {
+        self.0.serialize_field(key, &Wrap(v))
+    }
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.0.skip_field(key)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+impl<S: ser::SerializeStructVariant> ser::SerializeStructVariant for Ser<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        v: &T,
+    ) -> Result<(), Self::Error> {
+        self.0.serialize_field(key, &Wrap(v))
+    }
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        self.0.skip_field(key)
+    }
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+/// Десериализатор JSON-дерева: строка "f32:..." в поле f32 - число по битам
+struct De(Value);
+
+impl<'de> de::Deserializer<'de> for De {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(v) => visitor.visit_bool(v),
+            Value::Number(v) => v.deserialize_any(visitor),
+            Value::String(v) => visitor.visit_string(v),
+            Value::Array(v) => visitor.visit_seq(Seq(v.into_iter())),
+            Value::Object(v) => visitor.visit_map(Obj {
+                iter: v.into_iter(),
+                value: None,
+            }),
+        }
+    }
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match &self.0 {
+            Value::String(str) => match f32_from_str(str) {
+                Some(v) => visitor.visit_f32(v),
+                None => self.deserialize_any(visitor),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.0 {
+            Value::String(variant) => visitor.visit_enum(Enum {
+                variant,
+                value: Value::Null,
+            }),
+            Value::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().expect("one key");
+                visitor.visit_enum(Enum { variant, value })
+            }
+            other => Err(de::Error::invalid_type(
+                de::Unexpected::Other(&other.to_string()),
+                &"enum",
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f64 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct Seq(std::vec::IntoIter<Value>);
+impl<'de> SeqAccess<'de> for Seq {
+    type Error = Error;
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        self.0.next().map(|v| seed.deserialize(De(v))).transpose()
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct Obj {
+    iter: <Map<String, Value> as IntoIterator>::IntoIter,
+    value: Option<Value>,
+}
+impl<'de> MapAccess<'de> for Obj {
+    type Error = Error;
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.iter.next() {
+            None => Ok(None),
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+        }
+    }
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.value.take() {
+            None => Err(de::Error::custom("value is missing")),
+            Some(value) => seed.deserialize(De(value)),
+        }
+    }
+}
+
+struct Enum {
+    variant: String,
+    value: Value,
+}
+impl<'de> EnumAccess<'de> for Enum {
+    type Error = Error;
+    type Variant = De;
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, De), Error> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, De(self.value)))
+    }
+}
+impl<'de> VariantAccess<'de> for De {
+    type Error = Error;
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.0 {
+            Value::Null => Ok(()),
+            _ => Err(de::Error::custom("expected unit variant")),
+        }
+    }
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Kind {
+        Unit,
+        Rect { b: f32, h: f32 },
+    }
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Test {
+        h: f32,
+        list: Vec<f32>,
+        opt: Option<f32>,
+        kind: Vec<Kind>,
+        name: String,
+    }
+
+    #[test]
+    fn json_non_finite() {
+        let nan = f32::from_bits(0x7fc0_0001);
+        let test = Test {
+            h: nan,
+            list: vec![3.3, f32::INFINITY, f32::NEG_INFINITY],
+            opt: Some(f32::NAN),
+            kind: vec![
+                Kind::Unit,
+                Kind::Rect {
+                    b: 0.4,
+                    h: f32::NAN,
+                },
+            ],
+            name: "f32:7fc00000".to_string(),
+        };
+        let json = to_string(&test).expect("couldn't serialize");
+        assert!(json.starts_with(r#"{"h":"f32:7fc00001","list":[3.3,"f32:7f800000","#));
+        let value: Value = serde_json::from_str(&json).expect("not json");
+        let back: Test = from_value(value).expect("couldn't deserialize");
+        assert_eq!(back.h.to_bits(), nan.to_bits());
+        assert_eq!(back.list[0], 3.3);
+        assert_eq!(back.list[1], f32::INFINITY);
+        assert_eq!(back.list[2], f32::NEG_INFINITY);
+        assert!(back.opt.expect("no opt").is_nan());
+        assert_eq!(back.kind[0], Kind::Unit);
+        assert_eq!(back.name, test.name);
+        let back: Test = from_reader(json.as_bytes()).expect("couldn't read");
+        assert_eq!(back.list[0], 3.3);
+    }
+    #[test]
+    fn json_errors() {
+        assert!(from_value::<f32>(Value::String("f32:7fc0".to_string())).is_err());
+        assert!(from_value::<f32>(Value::Null).is_err());
+        assert!(from_value::<Kind>(serde_json::json!({"Other": null})).is_err());
+    }
+}
@@ -4,6 +4,25 @@
 //!
 //! Парсим файл, затем собираем его обратно. Модули _raw для анализа фрагментов исходного файла
 //!
+//! Команды:
+//! * `parse_chg json <file.chg> [out.json]` - выгрузка здания в JSON
+//! * `parse_chg text <file.chg> [out.txt]` - текстовый формат, без out.txt - в stdout
+//! * `parse_chg chg <file.json|file.txt> [out.chg]` - сборка *.chg из JSON или текста
+//! * `parse_chg csv <file.chg> [out_dir]` - ведомости колонн, стен, балок, свай, фундаментов
+//! * `parse_chg sqlite <file.chg> [out.sqlite]` - выгрузка в базу SQLite
+//! * `parse_chg salvage <file.chg> [out.chg]` - восстановление поврежденного файла: отчет
+//!   о повреждениях, с out.chg - запись того, что удалось прочитать
+//! * `parse_chg scan <dir> [out.csv]` - сводка по всем *.chg каталога, без out.csv - в stdout
+//! * `parse_chg query <file.chg> <etazh_num> near <x> <y> <r> | wall <x> <y> |
+//!   line <x1> <y1> <x2> <y2> | polygon <x1> <y1> <x2> <y2> <x3> <y3> ...` - элементы этажа
+//!   в радиусе от точки, ближайшая стена, элементы на отрезке или в многоугольнике
+//! * `parse_chg lira <file.chg> <file.ald> <out_dir> [filter.xml|filter.toml]` - номера
+//!   КЭ ЛИРА элементов в разрезах, углы и имена разрезов
+//! * `parse_chg diff <old.chg> <new.chg>` - изменения элементов, разрезов и сигнатур
+//!
+//! Просмотр изменений моделей в git:
+//! `git config diff.chg.textconv "parse_chg text"` и строка `*.chg diff=chg` в .gitattributes
+//!
 //! <hr/>
 
 #![recursion_limit = "128"]
@@ -15,21 +34,199 @@ extern crate byteorder;
 extern crate core;
 extern crate walkdir;
 
+mod diff;
+mod geometry;
+mod json;
 mod read_write;
+mod scan;
+mod schedule;
 mod sig;
 mod slits_for_lira;
+mod spatial;
+mod sqlite;
 mod tests;
+mod text;
 
-use crate::read_write::{read_file, read_file_raw, write_by_file_raw, write_recognize_sig};
+use crate::read_write::{
+    read_file, read_file_raw, read_json, read_salvage, read_text, write_by_file_raw, write_file,
+    write_json, write_recognize_sig,
+};
 use std::path::Path;
+use std::process::exit;
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        None => analyse(),
+        Some("json") => {
+            let input = Path::new(arg(&args, 2));
+            let output = match args.get(3) {
+                None => input.with_extension("json"),
+                Some(out) => Path::new(out).to_path_buf(),
+            };
+            write_json(&read_file(input), &output);
+        }
+        Some("chg") => {
+            let input = Path::new(arg(&args, 2));
+            let output = match args.get(3) {
+                None => input.with_extension("chg"),
+                Some(out) => Path::new(out).to_path_buf(),
+            };
+            let building = match input.extension() {
+                Some(ext) if ext == "txt" => read_text(input),
+                _ => read_json(input),
+            };
+            write_file(&building, &output);
+        }
+        Some("text") => {
+            let text = text::to_text(&read_file(Path::new(arg(&args, 2))));
+            match args.get(3) {
+                None => print!("{}", text),
+                Some(out) => {
+                    if let Err(why) = std::fs::write(out, text) {
+                        panic!("couldn't write {}: {}", out, why)
+                    }
+                }
+            }
+        }
+        Some("csv") => {
+            let input = Path::new(arg(&args, 2));
+            let output = match args.get(3) {
+                None => input.with_extension(""),
+                Some(out) => Path::new(out).to_path_buf(),
+            };
+            schedule::write_schedules(&read_file(input), &output);
+        }
+        Some("sqlite") => {
+            let input = Path::new(arg(&args, 2));
+            let output = match args.get(3) {
+                None => input.with_extension("sqlite"),
+                Some(out) => Path::new(out).to_path_buf(),
+            };
+            if let Err(why) = sqlite::write_sqlite(&read_file(input), &output) {
+                eprintln!("{}", why);
+                exit(1)
+            }
+        }
+        Some("salvage") => {
+            let salvage = read_salvage(Path::new(arg(&args, 2)));
+            print!("{}", salvage);
+            if let Some(out) = args.get(3) {
+                write_file(&salvage, Path::new(out));
+            }
+        }
+        Some("scan") => {
+            let dir = Path::new(arg(&args, 2));
+            scan::set_quiet_hook();
+            let result = match args.get(3) {
+                None => scan::scan(dir, std::io::stdout()),
+                Some(out) => match std::fs::File::create(out) {
+                    Err(why) => panic!("couldn't create {}: {}", out, why),
+                    Ok(file) => scan::scan(dir, file),
+                },
+            };
+            if let Err(why) = result {
+                panic!("couldn't write summary: {}", why)
+            }
+        }
+        Some("query") => query(&args),
+        Some("lira") => {
+            let filter = match args.get(5) {
+                None => Ok(slits_for_lira::Filter::default()),
+                Some(path) => slits_for_lira::Filter::read(Path::new(path)),
+            };
+            let result = filter.and_then(|filter| {
+                slits_for_lira::write_slits_for_lira(
+                    Path::new(arg(&args, 2)),
+                    Path::new(arg(&args, 3)),
+                    Path::new(arg(&args, 4)),
+                    &filter,
+                )
+            });
+            if let Err(why) = result {
+                eprintln!("{}", why);
+                exit(1)
+            }
+        }
+        Some("diff") => {
+            let old = read_file(Path::new(arg(&args, 2)));
+            let new = read_file(Path::new(arg(&args, 3)));
+            let diff = diff::diff(&old, &new);
+            if diff.is_empty() {
+                println!("no changes");
+            }
+            print!("{}", diff);
+        }
+        Some(_) => usage(),
+    }
+}
+
+/// Разбор тестового файла посигнатурно в "out/" (без аргументов)
+fn analyse() {
     let input = Path::new(r"test_cases/Угольный_2этап_27.chg");
     let building_s = read_file_raw(input);
     let building = read_file(input);
     write_by_file_raw(&building_s);
     write_recognize_sig();
     println!("{}", &building);
+}
+
+/// Запрос к пространственному индексу этажа
+fn query(args: &[String]) {
+    let building = read_file(Path::new(arg(args, 2)));
+    let etazh_num: u16 = arg(args, 3).parse().unwrap_or_else(|_| usage());
+    let storey = match building
+        .storeys()
+        .into_iter()
+        .find(|s| s.etazh_num == etazh_num)
+    {
+        None => panic!("no storey {}", etazh_num),
+        Some(storey) => storey.index,
+    };
+    let numbers: Vec<f32> = args
+        .iter()
+        .skip(5)
+        .map(|a| a.parse().unwrap_or_else(|_| usage()))
+        .collect();
+    let points: Vec<sig::element::Point> = numbers
+        .chunks_exact(2)
+        .map(|c| geometry::point(c[0], c[1]))
+        .collect();
+    let index = spatial::SpatialIndex::new(&building);
+    let found = match (arg(args, 4), points.as_slice(), numbers.len()) {
+        ("near", [p, ..], 3) => index.within(storey, p, numbers[2]),
+        ("wall", [p], 2) => index.nearest_wall(storey, p).into_iter().collect(),
+        ("line", [p1, p2], 4) => index.crossing(storey, p1, p2),
+        ("polygon", polygon, n) if n >= 6 && n % 2 == 0 => index.intersecting(storey, polygon),
+        _ => usage(),
+    };
+    for element in found.iter() {
+        println!("{} {}", element.id, element.footprint());
+    }
+}
+
+fn arg(args: &[String], n: usize) -> &str {
+    match args.get(n) {
+        None => usage(),
+        Some(arg) => arg,
+    }
+}
 
-    //slits_for_lira::write_slits_for_lira();
+fn usage() -> ! {
+    eprintln!("usage: parse_chg json <file.chg> [out.json]");
+    eprintln!("       parse_chg text <file.chg> [out.txt]");
+    eprintln!("       parse_chg chg <file.json|file.txt> [out.chg]");
+    eprintln!("       parse_chg csv <file.chg> [out_dir]");
+    eprintln!("       parse_chg sqlite <file.chg> [out.sqlite]");
+    eprintln!("       parse_chg salvage <file.chg> [out.chg]");
+    eprintln!("       parse_chg scan <dir> [out.csv]");
+    eprintln!("       parse_chg query <file.chg> <etazh_num> near <x> <y> <r>");
+    eprintln!("       parse_chg query <file.chg> <etazh_num> wall <x> <y>");
+    eprintln!("       parse_chg query <file.chg> <etazh_num> line <x1> <y1> <x2> <y2>");
+    eprintln!(
+        "       parse_chg query <file.chg> <etazh_num> polygon <x1> <y1> <x2> <y2> <x3> <y3> ..."
+    );
+    eprintln!("       parse_chg lira <file.chg> <file.ald> <out_dir> [filter.xml|filter.toml]");
+    eprintln!("       parse_chg diff <old.chg> <new.chg>");
+    exit(1)
 }
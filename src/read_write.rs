@@ -3,6 +3,7 @@
 //use std::error::Error;
 use std::fs::File;
 use std::io::prelude::*;
+use std::io::{BufReader, BufWriter};
 use std::path::Path;
 //use nom::{le_u64, le_u16, le_u8, le_f32};
 //use nom::IResult;
@@ -13,6 +14,8 @@ use crate::sig::*;
 //use std::borrow::Borrow;
 
 /// Чтение *.chg файла (данные как переменные)
+///
+/// Нераспознанный хвост файла сохраняется в Building::leftover, сообщение о нем - в stderr.
 pub fn read_file(path: &Path) -> building::Building {
     let display = path.display();
     let mut file = match File::open(&path) {
@@ -23,15 +26,22 @@ pub fn read_file(path: &Path) -> building::Building {
     if let Err(why) = file.read_to_end(&mut original_in) {
         panic!("couldn't read {}: {}", display, why)
     };
-    let building = match building::read_original(&original_in) {
+    let (rest, mut building) = match building::read_original(&original_in) {
         Err(why) => panic!("parse error: {:?}", why.to_string()),
         Ok(building) => building,
     };
-
-    if !building.0.is_empty() {
-        println!("remainder of parsing: {:?}", building.0);
+    if !rest.is_empty() {
+        eprintln!("{}: remainder of parsing: {} bytes", display, rest.len());
+        building.leftover = rest.to_vec();
     };
-    building.1
+    building
+}
+/// Чтение поврежденного *.chg файла: все, что читается, и отчет о повреждениях
+pub fn read_salvage(path: &Path) -> salvage::Salvage {
+    match std::fs::read(path) {
+        Err(why) => panic!("couldn't read {}: {}", path.display(), why),
+        Ok(original_in) => salvage::salvage(&original_in),
+    }
 }
 /// Чтение *.chg файла (данные как вектор байт)
 ///
@@ -161,6 +171,59 @@ pub fn write_by_file_raw(building: &building_raw::Building) {
     write_sig(Some(building));
 }
 
+/// Запись здания в JSON
+///
+/// Все сигнатуры и элементы этажей с именованными полями, нераспознанные байты - hex-строкой.
+pub fn write_json(building: &building::Building, path: &Path) {
+    let display = path.display();
+    let file = match File::create(path) {
+        Err(why) => panic!("couldn't create {}: {}", display, why),
+        Ok(file) => file,
+    };
+    if let Err(why) = crate::json::to_writer_pretty(BufWriter::new(file), building) {
+        panic!("couldn't write {}: {}", display, why)
+    };
+}
+/// Чтение здания из JSON, записанного write_json
+pub fn read_json(path: &Path) -> building::Building {
+    let display = path.display();
+    let file = match File::open(path) {
+        Err(why) => panic!("couldn't open {}: {}", display, why),
+        Ok(file) => file,
+    };
+    let mut building: building::Building = match crate::json::from_reader(BufReader::new(file)) {
+        Err(why) => panic!("couldn't parse {}: {}", display, why),
+        Ok(building) => building,
+    };
+    if let Err(why) = building.recount() {
+        panic!("couldn't parse {}: {}", display, why)
+    }
+    building
+}
+/// Чтение здания из текстового формата (см. text)
+pub fn read_text(path: &Path) -> building::Building {
+    let display = path.display();
+    let text = match std::fs::read_to_string(path) {
+        Err(why) => panic!("couldn't read {}: {}", display, why),
+        Ok(text) => text,
+    };
+    match crate::text::from_text(&text) {
+        Err(why) => panic!("couldn't parse {}: {}", display, why),
+        Ok(building) => building,
+    }
+}
+/// Запись сигнатуры (или всего здания) в указанный файл
+pub fn write_file<T: HasWrite>(sig: &T, path: &Path) {
+    let display = path.display();
+    let mut file = match File::create(path) {
+        Err(why) => panic!("couldn't create {}: {}", display, why),
+        Ok(file) => file,
+    };
+    if let Err(why) = file.write_all(&sig.write()) {
+        panic!("couldn't write {}: {}", display, why)
+    };
+}
+
 /*
 pub fn parse_rab_e(source: &Vec<u8>) -> IResult<&[u8], Node> {
     read_rab_e_node(source)
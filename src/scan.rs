@@ -0,0 +1,263 @@
+//! Проверка архива *.chg
+//!
+//! Обход каталога, разбор каждого *.chg в параллельных потоках и сводка по строке
+//! на файл в CSV: тип файла, результат разбора (место ошибки - смещение от начала
+//! файла), число этажей и элементов, наличие результатов расчета, остаток разбора.
+use crate::sig::building::{read_original, Building};
+use rayon::prelude::*;
+use std::panic;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Сводка по файлу
+#[derive(Debug)]
+pub struct Summary {
+    pub path: PathBuf,
+    pub file_type: String,
+    pub status: Status,
+    pub storeys: usize,
+    pub elements: [usize; 9], //Число элементов по типам в порядке Kind::ALL
+    pub slits: usize,
+    pub nodesres_fe: bool,
+    pub elemsres_fe: bool,
+    pub leftover: usize, //Остаток разбора, байт
+}
+/// Результат разбора
+#[derive(Debug, PartialEq)]
+pub enum Status {
+    Ok,
+    Error { offset: usize, kind: String }, //Ошибка nom, смещение от начала файла
+    Panic(String),                         //Паника при разборе
+    Io(String),                            //Файл не прочитан
+}
+impl Status {
+    fn name(&self) -> &str {
+        match self {
+            Status::Ok => "ok",
+            Status::Error { .. } => "error",
+            Status::Panic(_) => "panic",
+            Status::Io(_) => "io",
+        }
+    }
+}
+
+const HEAD: [&str; 20] = [
+    "path",
+    "file_type",
+    "status",
+    "error_offset",
+    "error",
+    "storeys",
+    "columns",
+    "walls",
+    "beams",
+    "slabs",
+    "loads",
+    "partitions",
+    "piles",
+    "f_slabs",
+    "f_beams",
+    "slits",
+    "nodesres_fe",
+    "elemsres_fe",
+    "leftover",
+    "size",
+];
+
+impl Summary {
+    fn new(path: &Path) -> Summary {
+        Summary {
+            path: path.to_path_buf(),
+            file_type: String::new(),
+            status: Status::Ok,
+            storeys: 0,
+            elements: [0; 9],
+            slits: 0,
+            nodesres_fe: false,
+            elemsres_fe: false,
+            leftover: 0,
+        }
+    }
+    fn fill(&mut self, building: &Building) {
+        self.file_type = building.file_type.to_string();
+        self.storeys = building.rab_e.len();
+        for element in building.elements() {
+            self.elements[element.kind() as usize] += 1;
+        }
+        self.slits = building.slits_slt.as_ref().map_or(0, |s| s.slits.len());
+        self.nodesres_fe = building.nodesres_fe.is_some();
+        self.elemsres_fe = building.elemsres_fe.is_some();
+    }
+    fn record(&self, size: usize) -> Vec<String> {
+        let (offset, error) = match &self.status {
+            Status::Ok => (String::new(), String::new()),
+            Status::Error { offset, kind } => (offset.to_string(), kind.clone()),
+            Status::Panic(why) | Status::Io(why) => (String::new(), why.clone()),
+        };
+        let mut out = vec![
+            self.path.display().to_string(),
+            self.file_type.clone(),
+            self.status.name().to_string(),
+            offset,
+            error,
+            self.storeys.to_string(),
+        ];
+        out.extend(self.elements.iter().map(|n| n.to_string()));
+        out.push(self.slits.to_string());
+        out.push(self.nodesres_fe.to_string());
+        out.push(self.elemsres_fe.to_string());
+        out.push(self.leftover.to_string());
+        out.push(size.to_string());
+        out
+    }
+}
+
+/// Разбор одного файла без паники
+pub fn scan_bytes(path: &Path, original_in: &[u8]) -> Summary {
+    let mut summary = Summary::new(path);
+    match panic::catch_unwind(|| read_original(original_in)) {
+        Ok(Ok((rest, building))) => {
+            summary.fill(&building);
+            summary.leftover = rest.len();
+        }
+        Ok(Err(nom::Err::Error((rest, kind)))) | Ok(Err(nom::Err::Failure((rest, kind)))) => {
+            summary.status = Status::Error {
+                offset: original_in.len() - rest.len(),
+                kind: format!("{:?}", kind),
+            };
+        }
+        Ok(Err(nom::Err::Incomplete(needed))) => {
+            summary.status = Status::Error {
+                offset: original_in.len(),
+                kind: format!("{:?}", needed),
+            };
+        }
+        Err(why) => {
+            let why = match why.downcast_ref::<&str>() {
+                Some(s) => s.to_string(),
+                None => match why.downcast_ref::<String>() {
+                    Some(s) => s.clone(),
+                    None => "unknown".to_string(),
+                },
+            };
+            summary.status = Status::Panic(why);
+        }
+    }
+    summary
+}
+
+/// Файлы *.chg в дереве каталогов
+fn chg_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("chg")))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Хук паник без вывода в stderr для потоков rayon: сообщения паник разбора
+/// попадают в сводку. Ставится один раз до scan, остальные паники - как обычно
+pub fn set_quiet_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if rayon::current_thread_index().is_none() {
+            hook(info)
+        }
+    }));
+}
+
+/// Сводка по всем *.chg каталога в CSV
+pub fn scan<W: std::io::Write>(dir: &Path, out: W) -> csv::Result<()> {
+    let files = chg_files(dir);
+    let records: Vec<Vec<String>> = files
+        .par_iter()
+        .map(|path| match std::fs::read(path) {
+            Err(why) => {
+                let mut summary = Summary::new(path);
+                summary.status = Status::Io(why.to_string());
+                summary.record(0)
+            }
+            Ok(original_in) => scan_bytes(path, &original_in).record(original_in.len()),
+        })
+        .collect();
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(HEAD)?;
+    for record in records.iter() {
+        writer.write_record(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::rab_e_sig_test::{building_test_sig, rab_e_test_sig, read_test_sig};
+
+    fn test_chg() -> Vec<u8> {
+        building_test_sig(&[
+            rab_e_test_sig(1, 3.0, &["test_sig/columns/column_box.test"], &[]),
+            rab_e_test_sig(2, 3.0, &[], &["test_sig/walls/wall.test"]),
+            read_test_sig("test_sig/slits/2slits.test"),
+        ])
+    }
+
+    /// Пустой временный каталог теста: номер процесса и имя теста, чтобы параллельные
+    /// запуски не мешали друг другу
+    fn test_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("parse_chg_{}_{}", std::process::id(), test));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("couldn't create dir");
+        dir
+    }
+
+    #[test]
+    fn scan_ok() {
+        let summary = scan_bytes(Path::new("a.chg"), &test_chg());
+        assert_eq!(summary.status, Status::Ok);
+        assert_eq!(summary.file_type, "BUILDER012");
+        assert_eq!(summary.storeys, 2);
+        assert_eq!(summary.elements[0], 1);
+        assert_eq!(summary.elements[1], 1);
+        assert_eq!(summary.slits, 2);
+        assert!(!summary.nodesres_fe);
+        assert_eq!(summary.leftover, 0);
+    }
+    #[test]
+    fn scan_leftover() {
+        let mut original_in = test_chg();
+        original_in.extend(b"garbage");
+        let summary = scan_bytes(Path::new("a.chg"), &original_in);
+        assert_eq!(summary.status, Status::Ok);
+        assert_eq!(summary.leftover, 7);
+    }
+    #[test]
+    fn scan_broken() {
+        let original_in = test_chg();
+        let summary = scan_bytes(Path::new("a.chg"), &original_in[..4]);
+        assert_ne!(summary.status, Status::Ok);
+        let record = summary.record(4);
+        assert_eq!(record.len(), HEAD.len());
+    }
+    #[test]
+    fn scan_dir() {
+        let dir = test_dir("scan_dir");
+        std::fs::create_dir_all(dir.join("sub")).expect("couldn't create dir");
+        std::fs::write(dir.join("a.chg"), test_chg()).expect("couldn't write");
+        std::fs::write(dir.join("sub").join("b.CHG"), b"BUILDER012").expect("couldn't write");
+        std::fs::write(dir.join("c.txt"), b"text").expect("couldn't write");
+        let mut out = vec![];
+        scan(&dir, &mut out).expect("couldn't scan");
+        let text = String::from_utf8(out).expect("not utf8");
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("path,file_type,status"));
+        assert!(lines[1].contains("a.chg,BUILDER012,ok,,,2,1,1,"));
+        assert!(lines[2].contains("b.CHG,BUILDER012,ok"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
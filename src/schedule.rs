@@ -0,0 +1,286 @@
+//! Ведомости элементов в CSV
+//!
+//! По строке на элемент: колонны, стены, балки, сваи, фундаменты. Первые колонки -
+//! этаж (имя, номер, отметка низа), номер элемента на этаже. Координаты в м,
+//! размеры сечений в см, как в файле.
+use crate::sig::building::Building;
+use crate::sig::storey::Storey;
+use std::path::Path;
+
+/// Ведомость: имя файла, заголовок, строки
+pub struct Schedule {
+    pub name: &'static str,
+    pub head: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+impl Schedule {
+    fn new(name: &'static str, head: &[&'static str]) -> Schedule {
+        let mut full = vec!["storey", "etazh_num", "level", "num"];
+        full.extend(head);
+        Schedule {
+            name,
+            head: full,
+            rows: vec![],
+        }
+    }
+    fn push(&mut self, storey: &Storey, num: usize, row: Vec<String>) {
+        let mut full = vec![
+            storey.title(),
+            storey.etazh_num.to_string(),
+            format!("{:.3}", storey.level),
+            num.to_string(),
+        ];
+        full.extend(row);
+        self.rows.push(full);
+    }
+    /// CSV в writer
+    pub fn write<W: std::io::Write>(&self, out: W) -> csv::Result<()> {
+        let mut writer = csv::Writer::from_writer(out);
+        writer.write_record(&self.head)?;
+        for row in self.rows.iter() {
+            writer.write_record(row)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Размеры сечений, колонки sec_b, sec_h...
+const SEC_DIMS: [&str; 10] = ["b", "h", "b1", "h1", "b2", "h2", "b3", "h3", "d", "t"];
+
+fn flag(value: bool) -> String {
+    if value { "1" } else { "0" }.to_string()
+}
+
+/// Ведомости здания
+pub fn schedules(building: &Building) -> Vec<Schedule> {
+    let mut columns = Schedule::new(
+        "columns",
+        &[
+            "x",
+            "y",
+            "fi",
+            "sec",
+            "sec_b",
+            "sec_h",
+            "sec_b1",
+            "sec_h1",
+            "sec_b2",
+            "sec_h2",
+            "sec_b3",
+            "sec_h3",
+            "sec_d",
+            "sec_t",
+            "mat",
+            "hinge_bottom",
+            "hinge_top",
+            "bearing",
+            "mu",
+        ],
+    );
+    let mut walls = Schedule::new(
+        "walls",
+        &[
+            "x1",
+            "y1",
+            "x2",
+            "y2",
+            "length",
+            "b",
+            "mat",
+            "op_num",
+            "op_area",
+            "reinforcement_wall",
+        ],
+    );
+    let mut beams = Schedule::new(
+        "beams",
+        &[
+            "x1", "y1", "x2", "y2", "length", "sec", "sec_b", "sec_h", "sec_b1", "sec_h1",
+            "sec_b2", "sec_h2", "sec_b3", "sec_h3", "sec_d", "sec_t", "mat", "hinge1", "hinge2",
+        ],
+    );
+    let mut piles = Schedule::new("piles", &["x", "y", "type", "params"]);
+    let mut founds = Schedule::new("founds", &["b", "l", "h"]);
+    for storey in building.storeys() {
+        let rab_e = storey.rab_e;
+        for (num, c) in rab_e.column.iter().enumerate() {
+            let (bottom, top) = c.get_hinges();
+            let mut row = vec![
+                c.get_point().x.to_string(),
+                c.get_point().y.to_string(),
+                c.get_fi().to_string(),
+                c.get_sec().kind().to_string(),
+            ];
+            row.extend(sec_dims(c.get_sec().dims()));
+            row.extend(vec![
+                c.get_mat().to_string(),
+                flag(bottom),
+                flag(top),
+                c.get_bearing().to_string(),
+                c.get_mu().to_string(),
+            ]);
+            columns.push(&storey, num, row);
+        }
+        for (num, w) in rab_e.wall.iter().enumerate() {
+            let (p1, p2) = (w.get_start_point(), w.get_end_point());
+            let op = w.get_openings();
+            let op_area: f32 = op.iter().map(|o| o.area()).sum();
+            let row = vec![
+                p1.x.to_string(),
+                p1.y.to_string(),
+                p2.x.to_string(),
+                p2.y.to_string(),
+                format!("{:.3}", length(p1.x, p1.y, p2.x, p2.y)),
+                w.get_b().to_string(),
+                w.get_mat().to_string(),
+                op.len().to_string(),
+                format!("{:.3}", op_area),
+                w.get_reinforcement().to_string(),
+            ];
+            walls.push(&storey, num, row);
+        }
+        for (num, b) in rab_e.beam.iter().enumerate() {
+            let (p1, p2) = (b.get_start_point(), b.get_end_point());
+            let (hinge1, hinge2) = b.get_hinges();
+            let mut row = vec![
+                p1.x.to_string(),
+                p1.y.to_string(),
+                p2.x.to_string(),
+                p2.y.to_string(),
+                format!("{:.3}", length(p1.x, p1.y, p2.x, p2.y)),
+                b.get_sec().kind().to_string(),
+            ];
+            row.extend(sec_dims(b.get_sec().dims()));
+            row.extend(vec![b.get_mat().to_string(), flag(hinge1), flag(hinge2)]);
+            beams.push(&storey, num, row);
+        }
+        for (num, p) in rab_e.pile.iter().enumerate() {
+            let row = vec![
+                p.get_point().x.to_string(),
+                p.get_point().y.to_string(),
+                p.kind().to_string(),
+                p.params(),
+            ];
+            piles.push(&storey, num, row);
+        }
+        for (num, f) in rab_e.f_wall.iter().enumerate() {
+            let (b, l, h) = f.get_size();
+            founds.push(
+                &storey,
+                num,
+                vec![b.to_string(), l.to_string(), h.to_string()],
+            );
+        }
+    }
+    vec![columns, walls, beams, piles, founds]
+}
+
+/// Размеры сечения по колонкам SEC_DIMS, пусто - размера нет у этого типа сечения
+fn sec_dims(dims: Vec<(&str, f32)>) -> Vec<String> {
+    SEC_DIMS
+        .iter()
+        .map(|name| match dims.iter().find(|(n, _)| n == name) {
+            Some((_, value)) => value.to_string(),
+            None => String::new(),
+        })
+        .collect()
+}
+
+fn length(x1: f32, y1: f32, x2: f32, y2: f32) -> f32 {
+    ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt()
+}
+
+/// Запись ведомостей в каталог: columns.csv, walls.csv, beams.csv, piles.csv, founds.csv
+pub fn write_schedules(building: &Building, dir: &Path) {
+    if let Err(why) = std::fs::create_dir_all(dir) {
+        panic!("couldn't create {}: {}", dir.display(), why)
+    }
+    for schedule in schedules(building).iter() {
+        let path = dir.join(format!("{}.csv", schedule.name));
+        let file = match std::fs::File::create(&path) {
+            Err(why) => panic!("couldn't create {}: {}", path.display(), why),
+            Ok(file) => file,
+        };
+        if let Err(why) = schedule.write(file) {
+            panic!("couldn't write {}: {}", path.display(), why)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::rab_e_sig_test::{rab_e_test_sig, rab_e_test_sig_full, read_test_building};
+
+    fn test_schedules(sigs: &[Vec<u8>]) -> Vec<Schedule> {
+        schedules(&read_test_building(sigs))
+    }
+
+    #[test]
+    fn schedule_rows() {
+        let schedules = test_schedules(&[rab_e_test_sig_full(1, 3.3)]);
+        let names: Vec<&str> = schedules.iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["columns", "walls", "beams", "piles", "founds"]);
+        for schedule in schedules.iter() {
+            assert!(!schedule.rows.is_empty(), "{}", schedule.name);
+            for row in schedule.rows.iter() {
+                assert_eq!(row.len(), schedule.head.len());
+            }
+        }
+    }
+    #[test]
+    fn schedule_wall() {
+        let schedules = test_schedules(&[
+            rab_e_test_sig(1, 3.3, &[], &[]),
+            rab_e_test_sig(2, 3.0, &[], &["test_sig/walls/wall.test"]),
+        ]);
+        let walls = &schedules[1];
+        assert_eq!(walls.rows.len(), 1);
+        let row = &walls.rows[0];
+        assert_eq!(row[1], "2");
+        assert_eq!(row[2], "3.300");
+        assert_eq!(row[11], "0");
+        let mut out = vec![];
+        walls.write(&mut out).expect("couldn't write csv");
+        let text = String::from_utf8(out).expect("not utf8");
+        assert!(text.starts_with("storey,etazh_num,level,num,x1,y1,x2,y2,length,b,"));
+        assert_eq!(text.lines().count(), 2);
+    }
+    #[test]
+    fn schedule_wall_openings() {
+        let schedules = test_schedules(&[rab_e_test_sig(
+            1,
+            3.0,
+            &[],
+            &["test_sig/walls/wall_opening_1.test"],
+        )]);
+        let row = &schedules[1].rows[0];
+        assert_eq!(row[11], "1");
+        let area: f32 = row[12].parse().expect("bad op_area");
+        assert!(area > 0.0);
+    }
+    #[test]
+    fn schedule_column_sec() {
+        let schedules = test_schedules(&[rab_e_test_sig(
+            1,
+            3.0,
+            &[
+                "test_sig/columns/column_circle.test",
+                "test_sig/columns/column_isec.test",
+            ],
+            &[],
+        )]);
+        let columns = &schedules[0];
+        let cell = |row: usize, name: &str| {
+            let n = columns.head.iter().position(|h| *h == name).expect(name);
+            columns.rows[row][n].as_str()
+        };
+        assert_eq!(cell(0, "sec"), "circle");
+        assert!(cell(0, "sec_d").parse::<f32>().expect("bad sec_d") > 0.0);
+        assert_eq!(cell(0, "sec_b"), "");
+        assert_eq!(cell(1, "sec"), "i_sec");
+        assert!(cell(1, "sec_h2").parse::<f32>().expect("bad sec_h2") > 0.0);
+        assert_eq!(cell(1, "sec_d"), "");
+    }
+}
@@ -1,12 +1,23 @@
+//! Результаты расчета стержней (barpbres.fe)
+//!
+//! Заголовок как у остальных сигнатур: имя в поле 13b и длина тела u64. В известных
+//! файлах длина 0, поэтому раньше сигнатура читалась как 10 байт после имени (хвост
+//! поля имени 2b и длина 8b). Теперь длина учитывается, source - все байты после имени:
+//! хвост поля, длина и тело. Формат тела (усилия N, Qy, Qz, Mx, My, Mz по сечениям и
+//! загружениям, связь с колоннами и балками) не расшифрован - нет образцов с непустым
+//! телом.
 use crate::sig::*;
 use nom::{
     bytes::complete::{tag, take},
+    number::complete::le_u64,
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct BarpbresFe {
+    #[serde(with = "crate::sig::hex")]
     source: Vec<u8>,
 }
 impl HasWrite for BarpbresFe {
@@ -27,7 +38,10 @@ impl fmt::Display for BarpbresFe {
 
 pub fn read_barpbres_fe(i: &[u8]) -> IResult<&[u8], BarpbresFe> {
     let (i, _) = tag("barpbres.fe")(i)?;
-    let (i, source) = take(10u8)(i)?;
+    //Длина тела после хвоста поля имени, source - вместе с хвостом и длиной
+    let (rest, _) = take(2u8)(i)?;
+    let (_, len) = le_u64(rest)?;
+    let (i, source) = take(10u64.saturating_add(len))(i)?;
     Ok((
         i,
         BarpbresFe {
@@ -35,3 +49,23 @@ pub fn read_barpbres_fe(i: &[u8]) -> IResult<&[u8], BarpbresFe> {
         },
     ))
 }
+
+#[test]
+fn barpbres_fe_empty_test() {
+    let mut original_in = b"barpbres.fe".to_vec();
+    original_in.extend(vec![0u8; 10]);
+    let (rest, sig) = read_barpbres_fe(&original_in).expect("couldn't read_barpbres_fe");
+    assert!(rest.is_empty());
+    assert_eq!(sig.source.len(), 10);
+    assert_eq!(original_in, sig.write());
+}
+#[test]
+fn barpbres_fe_body_test() {
+    use crate::tests::rab_e_sig_test::raw_test_sig;
+    let mut original_in = raw_test_sig("barpbres.fe", &[1, 2, 3, 4, 5]);
+    original_in.extend(b"next");
+    let (rest, sig) = read_barpbres_fe(&original_in).expect("couldn't read_barpbres_fe");
+    assert_eq!(rest, b"next");
+    assert_eq!(&original_in[..original_in.len() - 4], &sig.write()[..]);
+    assert!(read_barpbres_fe(&original_in[..20]).is_err());
+}
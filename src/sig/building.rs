@@ -40,9 +40,10 @@ use crate::sig::zagrcmbs_zc::read_zagrcmbs_zc;
 use crate::sig::zagrs_fe::read_zagrs_fe;
 use crate::sig::*;
 use nom::{combinator::opt, IResult};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Building {
     pub file_type: file_type::FileType,
     pub barpbres_fe: Option<barpbres_fe::BarpbresFe>,
@@ -84,10 +85,53 @@ pub struct Building {
     pub wind_rsp: Option<wind_rsp::WindRsp>,
     pub zagrcmbs_zc: Option<zagrcmbs_zc::ZagrcmbsZc>,
     pub zagrs_fe: Option<zagrs_fe::ZagrsFe>,
+    //Нераспознанные байты после всех сигнатур, записываются в конец файла
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "crate::sig::hex"
+    )]
+    pub leftover: Vec<u8>,
 }
 
 impl HasWrite for Building {
     fn write(&self) -> Vec<u8> {
+        let mut rab_e = vec![];
+        for rab_e_n in (&self.rab_e).iter() {
+            rab_e.extend(rab_e_n.write());
+        }
+        self.write_with_rab_e(&rab_e)
+    }
+    fn name(&self) -> &str {
+        "BUILDING.chg"
+    }
+}
+impl Building {
+    /// Здание с образцами элементов этажа и разреза, см. RabE::sample
+    pub fn sample() -> Building {
+        Building {
+            rab_e: vec![rab_e::rab_e::RabE::sample()],
+            slits_slt: Some(slits_slt::SlitsSlt {
+                slits: vec![Default::default()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+    /// Пересчет счетчиков этажей и разрезов по длинам векторов (см. RabE::recount)
+    pub fn recount(&mut self) -> Result<(), String> {
+        for rab_e in self.rab_e.iter_mut() {
+            rab_e.recount()?;
+        }
+        if let Some(slits_slt) = &mut self.slits_slt {
+            slits_slt.recount()?;
+        }
+        Ok(())
+    }
+    /// Запись здания с готовыми байтами этажей rab.e
+    ///
+    /// Для восстановленного файла, где часть этажей осталась сырой (RabERaw).
+    pub(crate) fn write_with_rab_e(&self, rab_e: &[u8]) -> Vec<u8> {
         let mut out = match &self.file_type {
             file_type::FileType::BUILDER012 => b"BUILDER012".to_vec(),
             file_type::FileType::BUILDER011 => b"BUILDER011".to_vec(),
@@ -116,9 +160,7 @@ impl HasWrite for Building {
         out.extend(trans_to_vec(&self.procalc_set));
         out.extend(trans_to_vec(&self.prores_use));
         out.extend(trans_to_vec(&self.rab_a0));
-        for rab_e_n in (&self.rab_e).iter() {
-            out.extend(rab_e_n.write());
-        }
+        out.extend(rab_e);
         out.extend(trans_to_vec(&self.rab_o0));
         out.extend(trans_to_vec(&self.rab_sdr));
         out.extend(trans_to_vec(&self.rab_zag));
@@ -135,11 +177,9 @@ impl HasWrite for Building {
         out.extend(trans_to_vec(&self.wind_rsp));
         out.extend(trans_to_vec(&self.zagrcmbs_zc));
         out.extend(trans_to_vec(&self.zagrs_fe));
+        out.extend(&self.leftover);
         out
     }
-    fn name(&self) -> &str {
-        "BUILDING.chg"
-    }
 }
 impl fmt::Display for Building {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -166,9 +206,9 @@ impl fmt::Display for Building {
         trans_writeln(f, &self.procalc_set)?;
         trans_writeln(f, &self.prores_use)?;
         trans_writeln(f, &self.rab_a0)?;
-        let vec = &self.rab_e;
-        for v in vec.iter() {
-            writeln!(f, "{}", v)?;
+        for storey in self.storeys().iter() {
+            writeln!(f, "{}", storey)?;
+            writeln!(f, "{}", storey.rab_e)?;
         }
         trans_writeln(f, &self.rab_o0)?;
         trans_writeln(f, &self.rab_sdr)?;
@@ -286,6 +326,7 @@ pub fn read_original(i: &[u8]) -> IResult<&[u8], Building> {
             wind_rsp,
             zagrcmbs_zc,
             zagrs_fe,
+            leftover: vec![],
         },
     ))
 }
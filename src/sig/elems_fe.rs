@@ -4,11 +4,14 @@ use nom::{
     number::complete::le_u64,
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ElemsFe {
+    #[serde(with = "crate::sig::hex")]
     flag_line: [u8; 4],
+    #[serde(with = "crate::sig::hex")]
     source: Vec<u8>,
 }
 impl HasWrite for ElemsFe {
@@ -1,12 +1,14 @@
 use nom::{bytes::complete::tag, IResult};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub enum FileType {
     BUILDER012, //monomakh-SAPR 2016
     BUILDER011, //monomakh-SAPR 2013
     CHARGE37,   //monomakh 4.5
-    ERROR,      //another title
+    #[default]
+    ERROR, //another title
 }
 impl fmt::Display for FileType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
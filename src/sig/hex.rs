@@ -0,0 +1,85 @@
+//! Байтовые поля в виде hex-строки для serde
+//!
+//! Используется через `#[serde(with = "crate::sig::hex")]` для нераспознанных байт (ws, source).
+//! Поля Vec<u8> фиксированной длины читаются через `deserialize_len`, иначе короткая строка
+//! из JSON дошла бы до записи и сломала срезы в HasWrite::write.
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+use std::convert::TryFrom;
+
+/// Байты в строку вида "0a1b2c"
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        out.push_str(&format!("{:02x}", b));
+    }
+    out
+}
+/// Строка вида "0a1b2c" в байты
+pub fn from_hex(str: &str) -> Option<Vec<u8>> {
+    if !str.len().is_multiple_of(2) || !str.is_ascii() {
+        return None;
+    }
+    let mut out = Vec::with_capacity(str.len() / 2);
+    for i in (0..str.len()).step_by(2) {
+        out.push(u8::from_str_radix(&str[i..i + 2], 16).ok()?);
+    }
+    Some(out)
+}
+
+pub fn serialize<S, T>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: AsRef<[u8]>,
+{
+    serializer.serialize_str(&to_hex(bytes.as_ref()))
+}
+
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<Vec<u8>>,
+{
+    let str = String::deserialize(deserializer)?;
+    let bytes = from_hex(&str).ok_or_else(|| D::Error::custom(format!("bad hex: {}", str)))?;
+    let len = bytes.len();
+    T::try_from(bytes).map_err(|_| D::Error::custom(format!("wrong bytes length: {}", len)))
+}
+
+/// Байты ровно длины N
+pub fn deserialize_len<'de, D, const N: usize>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let bytes: Vec<u8> = deserialize(deserializer)?;
+    if bytes.len() != N {
+        return Err(D::Error::custom(format!(
+            "wrong bytes length: {}, expected {}",
+            bytes.len(),
+            N
+        )));
+    }
+    Ok(bytes)
+}
+
+#[test]
+fn hex_test() {
+    let bytes = vec![0u8, 1, 15, 16, 171, 255];
+    assert_eq!(to_hex(&bytes), "00010f10abff");
+    assert_eq!(from_hex("00010f10abff"), Some(bytes));
+    assert_eq!(from_hex("0g"), None);
+    assert_eq!(from_hex("abc"), None);
+}
+#[test]
+fn hex_len_test() {
+    #[derive(serde::Deserialize)]
+    struct Ws {
+        #[serde(deserialize_with = "deserialize_len::<_, 2>")]
+        ws: Vec<u8>,
+    }
+    let ws: Ws = serde_json::from_str(r#"{"ws":"0a0b"}"#).expect("couldn't deserialize");
+    assert_eq!(ws.ws, vec![10, 11]);
+    let err = serde_json::from_str::<Ws>(r#"{"ws":"00"}"#)
+        .err()
+        .expect("short ws");
+    assert!(err.to_string().contains("expected 2"));
+}
@@ -2,6 +2,7 @@
 //!
 //! Файл *.chg разбит тексовыми вставками на отдельные блоки.
 mod file_type;
+mod hex;
 
 mod barpbres_fe;
 mod bkngwl_bnw;
@@ -45,8 +46,14 @@ mod zagrs_fe;
 
 pub mod building;
 pub mod building_raw;
+pub mod salvage;
+pub mod storey;
+
+pub use rab_e::element;
+pub use slits_slt::Slit;
 
 use byteorder::{LittleEndian, WriteBytesExt};
+use std::convert::TryFrom;
 
 /// Преобразование в байты
 pub trait HasWrite {
@@ -56,6 +63,10 @@ pub trait HasWrite {
     fn name(&self) -> &str;
 }
 
+/// Счетчик элементов по длине вектора
+fn count_num(name: &str, len: usize) -> Result<u16, String> {
+    u16::try_from(len).map_err(|_| format!("too many {}: {}", name, len))
+}
 /// Смещение, до конца данных в сигнатуре в байты
 fn offset(len: usize) -> [u8; 8] {
     let offset = len as u64;
@@ -7,9 +7,10 @@ use nom::{
     number::complete::{le_f32, le_u16, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 /// Балка
 pub struct Beam {
     p1: Point,  //Координаты 1 точки
@@ -29,7 +30,11 @@ pub struct Beam {
     cons_3: u8,   //Всегда 1
     mat: u16,     //Номер материала
     //30b
-    sec: Sec,    //Сечение
+    sec: Sec, //Сечение
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 59>"
+    )]
     ws: Vec<u8>, //59b
 }
 impl HasWrite for Beam {
@@ -69,12 +74,32 @@ impl fmt::Display for Beam {
     }
 }
 impl Beam {
+    /// Образцы по одному на каждый тип сечения
+    pub(crate) fn samples() -> Vec<Beam> {
+        Sec::samples()
+            .into_iter()
+            .map(|sec| Beam {
+                sec,
+                ..Default::default()
+            })
+            .collect()
+    }
     pub fn get_start_point(&self) -> &Point {
         &self.p1
     }
     pub fn get_end_point(&self) -> &Point {
         &self.p2
     }
+    /// Шарниры: (1 точка, 2 точка)
+    pub fn get_hinges(&self) -> (bool, bool) {
+        (self.hinge1_flag & 16 != 0, self.m_flag & 4 != 0)
+    }
+    pub fn get_mat(&self) -> u16 {
+        self.mat
+    }
+    pub fn get_sec(&self) -> &Sec {
+        &self.sec
+    }
 }
 
 pub fn read_beam(i: &[u8]) -> IResult<&[u8], Beam> {
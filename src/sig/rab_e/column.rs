@@ -7,9 +7,10 @@ use nom::{
     number::complete::{le_f32, le_i16, le_i32, le_u16, le_u32, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Column {
     p: Point,    //Координаты, м
     agt: u8,     //Генерировать АЖТ. 0=нет, 120=да
@@ -44,7 +45,11 @@ pub struct Column {
     flag_hinge: u8, //Шарнир с плитами. 0=нет, 1=низ, 2=верх, 3=низ и верх
     mat: u16,       //Номер материала стены
     //29b WS
-    sec: Sec,    //Тип сечения
+    sec: Sec, //Тип сечения
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 59>"
+    )]
     ws: Vec<u8>, //59b
 }
 impl HasWrite for Column {
@@ -99,6 +104,42 @@ impl fmt::Display for Column {
     }
 }
 
+impl Column {
+    /// Образцы по одному на каждый тип сечения
+    pub(crate) fn samples() -> Vec<Column> {
+        Sec::samples()
+            .into_iter()
+            .map(|sec| Column {
+                sec,
+                ..Default::default()
+            })
+            .collect()
+    }
+    pub fn get_point(&self) -> &Point {
+        &self.p
+    }
+    /// Угол поворота, радианы
+    pub fn get_fi(&self) -> f32 {
+        self.fi
+    }
+    pub fn get_bearing(&self) -> u8 {
+        self.bearing
+    }
+    /// Шарниры с плитами: (низ, верх)
+    pub fn get_hinges(&self) -> (bool, bool) {
+        (self.flag_hinge & 1 != 0, self.flag_hinge & 2 != 0)
+    }
+    pub fn get_mu(&self) -> f32 {
+        self.mu
+    }
+    pub fn get_mat(&self) -> u16 {
+        self.mat
+    }
+    pub fn get_sec(&self) -> &Sec {
+        &self.sec
+    }
+}
+
 pub fn read_column(i: &[u8]) -> IResult<&[u8], Column> {
     let (i, p) = read_point(i)?;
     let (i, agt) = le_u8(i)?;
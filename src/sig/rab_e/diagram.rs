@@ -6,9 +6,10 @@ use nom::{
     number::complete::{le_f32, le_i16, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Diagram {
     load_time: u8, //Длительность загружения. 0=постоянное, 1=длительное, 2=кратковременное, 200=ветер1, 201=ветер2
     force_type: u8, //Тип нагрузки. 1=сосредоточенная, 4=погонная, 5=момент
@@ -21,6 +22,10 @@ pub struct Diagram {
     force_direction: u8, //Направление приложения силы. 0=вертикально, 1=момент, 2=горизонтально
     cons_1: i16,   //Всегда -1
     //10b WS
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 10>"
+    )]
     ws: Vec<u8>, //10b
 }
 impl HasWrite for Diagram {
@@ -0,0 +1,364 @@
+//! Элементы этажей с общим интерфейсом
+//!
+//! Колонны, стены, балки, плиты, фундаментные плиты и балки, перегородки, сваи и
+//! нагрузки перебираются одним циклом. Элемент адресуется ElementId: номер этажа
+//! снизу (как в Building::storeys), тип и номер элемента в массиве этажа.
+use crate::sig::building::Building;
+use crate::sig::rab_e::beam::Beam;
+use crate::sig::rab_e::column::Column;
+use crate::sig::rab_e::f_beam::FBeam;
+use crate::sig::rab_e::f_slab::FSlab;
+use crate::sig::rab_e::load::Load;
+use crate::sig::rab_e::part::Partition;
+use crate::sig::rab_e::pile::Pile;
+use crate::sig::rab_e::rab_e::RabE;
+use crate::sig::rab_e::slab::Slab;
+use crate::sig::rab_e::wall::Wall;
+pub use crate::sig::rab_e::Point;
+use std::fmt;
+use std::ops::Range;
+
+/// Тип элемента
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Kind {
+    Column,
+    Wall,
+    Beam,
+    Slab,
+    Load,
+    Partition,
+    Pile,
+    FSlab,
+    FBeam,
+}
+impl Kind {
+    /// Все типы в порядке перебора
+    pub const ALL: [Kind; 9] = [
+        Kind::Column,
+        Kind::Wall,
+        Kind::Beam,
+        Kind::Slab,
+        Kind::Load,
+        Kind::Partition,
+        Kind::Pile,
+        Kind::FSlab,
+        Kind::FBeam,
+    ];
+    /// Имя массива в RabE
+    pub fn name(&self) -> &str {
+        match self {
+            Kind::Column => "column",
+            Kind::Wall => "wall",
+            Kind::Beam => "beam",
+            Kind::Slab => "slab",
+            Kind::Load => "load",
+            Kind::Partition => "part",
+            Kind::Pile => "pile",
+            Kind::FSlab => "f_slab",
+            Kind::FBeam => "f_beam",
+        }
+    }
+    /// Количество элементов типа на этаже
+    fn len(&self, rab_e: &RabE) -> usize {
+        match self {
+            Kind::Column => rab_e.column.len(),
+            Kind::Wall => rab_e.wall.len(),
+            Kind::Beam => rab_e.beam.len(),
+            Kind::Slab => rab_e.slab.len(),
+            Kind::Load => rab_e.load.len(),
+            Kind::Partition => rab_e.part.len(),
+            Kind::Pile => rab_e.pile.len(),
+            Kind::FSlab => rab_e.f_slab.len(),
+            Kind::FBeam => rab_e.f_beam.len(),
+        }
+    }
+}
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Адрес элемента: этаж (порядковый номер снизу, с 0), тип, номер на этаже (с 0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ElementId(pub usize, pub Kind, pub usize);
+impl fmt::Display for ElementId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "storey {} {}[{}]", &self.0, &self.1, &self.2)
+    }
+}
+
+/// Ссылка на элемент своего типа
+#[derive(Debug, Clone, Copy)]
+pub enum Item<'a> {
+    Column(&'a Column),
+    Wall(&'a Wall),
+    Beam(&'a Beam),
+    Slab(&'a Slab),
+    Load(&'a Load),
+    Partition(&'a Partition),
+    Pile(&'a Pile),
+    FSlab(&'a FSlab),
+    FBeam(&'a FBeam),
+}
+
+/// Геометрия элемента в плане, м
+#[derive(Debug, Clone, PartialEq)]
+pub enum Footprint {
+    Point(Point),
+    Line(Point, Point),
+    Polygon(Vec<Point>), //Внешний контур
+    None,                //Контур не найден (нет полилиний или узлов)
+}
+impl Footprint {
+    /// Точки геометрии
+    pub fn points(&self) -> Vec<Point> {
+        match self {
+            Footprint::Point(p) => vec![*p],
+            Footprint::Line(p1, p2) => vec![*p1, *p2],
+            Footprint::Polygon(vec) => vec.clone(),
+            Footprint::None => vec![],
+        }
+    }
+}
+impl fmt::Display for Footprint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Footprint::Point(p) => write!(f, "POINT ({} {})", p.x, p.y),
+            Footprint::Line(p1, p2) => {
+                write!(f, "LINESTRING ({} {}, {} {})", p1.x, p1.y, p2.x, p2.y)
+            }
+            Footprint::Polygon(vec) => {
+                let points: Vec<String> = vec
+                    .iter()
+                    .chain(vec.first())
+                    .map(|p| format!("{} {}", p.x, p.y))
+                    .collect();
+                write!(f, "POLYGON (({}))", points.join(", "))
+            }
+            Footprint::None => write!(f, "POINT EMPTY"),
+        }
+    }
+}
+
+/// Габаритный прямоугольник, м
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+/// Элемент этажа
+#[derive(Debug, Clone, Copy)]
+pub struct Element<'a> {
+    pub id: ElementId,
+    pub item: Item<'a>,
+    rab_e: &'a RabE,
+}
+impl<'a> Element<'a> {
+    pub fn kind(&self) -> Kind {
+        self.id.1
+    }
+    /// Порядковый номер этажа снизу, с 0
+    pub fn storey(&self) -> usize {
+        self.id.0
+    }
+    /// Номер элемента в массиве этажа, с 0
+    pub fn index(&self) -> usize {
+        self.id.2
+    }
+    /// Номер материала. У свай и нагрузок нет
+    pub fn mat(&self) -> Option<u16> {
+        match self.item {
+            Item::Column(e) => Some(e.get_mat()),
+            Item::Wall(e) => Some(e.get_mat()),
+            Item::Beam(e) => Some(e.get_mat()),
+            Item::Slab(e) => Some(e.get_mat()),
+            Item::Partition(e) => Some(e.get_mat()),
+            Item::FSlab(e) => Some(e.get_mat()),
+            Item::FBeam(e) => Some(e.get_mat()),
+            Item::Load(_) | Item::Pile(_) => None,
+        }
+    }
+    /// Толщина стены или плиты, см
+    pub fn thickness(&self) -> Option<f32> {
+        match self.item {
+            Item::Wall(e) => Some(e.get_b()),
+            Item::Slab(e) => Some(e.get_b()),
+            Item::FSlab(e) => Some(e.get_b()),
+            _ => None,
+        }
+    }
+    /// Тип сечения колонны или балки
+    pub fn section(&self) -> Option<&'a str> {
+        match self.item {
+            Item::Column(e) => Some(e.get_sec().kind()),
+            Item::Beam(e) => Some(e.get_sec().kind()),
+            _ => None,
+        }
+    }
+    /// Геометрия в плане: точка, отрезок по оси или внешний контур
+    pub fn footprint(&self) -> Footprint {
+        match self.item {
+            Item::Column(e) => Footprint::Point(*e.get_point()),
+            Item::Pile(e) => Footprint::Point(*e.get_point()),
+            Item::Wall(e) => Footprint::Line(*e.get_start_point(), *e.get_end_point()),
+            Item::Beam(e) => Footprint::Line(*e.get_start_point(), *e.get_end_point()),
+            Item::Partition(e) => Footprint::Line(*e.get_start_point(), *e.get_end_point()),
+            Item::FBeam(e) => Footprint::Line(*e.get_start_point(), *e.get_end_point()),
+            Item::Slab(e) => self.contour(e.get_polys()),
+            Item::FSlab(e) => self.contour(e.get_polys()),
+            Item::Load(e) => {
+                let points = self.nodes(e.get_nodes());
+                match points.len() {
+                    0 => Footprint::None,
+                    1 => Footprint::Point(points[0]),
+                    2 => Footprint::Line(points[0], points[1]),
+                    _ => Footprint::Polygon(points),
+                }
+            }
+        }
+    }
+    /// Габаритный прямоугольник геометрии в плане
+    pub fn bbox(&self) -> Option<BBox> {
+        let points = self.footprint().points();
+        let first = points.first()?;
+        let mut bbox = BBox {
+            min: *first,
+            max: *first,
+        };
+        for p in points.iter() {
+            bbox.min.x = bbox.min.x.min(p.x);
+            bbox.min.y = bbox.min.y.min(p.y);
+            bbox.max.x = bbox.max.x.max(p.x);
+            bbox.max.y = bbox.max.y.max(p.y);
+        }
+        Some(bbox)
+    }
+    /// Точки узлов этажа по номерам, номера вне массива пропускаются
+    fn nodes(&self, range: Range<usize>) -> Vec<Point> {
+        range
+            .filter_map(|n| self.rab_e.node.get(n))
+            .map(|node| *node.get_point())
+            .collect()
+    }
+    /// Первый контур (не отверстие) из полилиний элемента
+    fn contour(&self, polys: Range<usize>) -> Footprint {
+        let poly = polys
+            .filter_map(|n| self.rab_e.poly.get(n))
+            .find(|poly| poly.is_contour());
+        match poly {
+            None => Footprint::None,
+            Some(poly) => match self.nodes(poly.get_nodes()) {
+                points if points.is_empty() => Footprint::None,
+                points => Footprint::Polygon(points),
+            },
+        }
+    }
+}
+
+/// Элемент этажа по типу и номеру
+fn item(rab_e: &RabE, kind: Kind, index: usize) -> Option<Item<'_>> {
+    Some(match kind {
+        Kind::Column => Item::Column(rab_e.column.get(index)?),
+        Kind::Wall => Item::Wall(rab_e.wall.get(index)?),
+        Kind::Beam => Item::Beam(rab_e.beam.get(index)?),
+        Kind::Slab => Item::Slab(rab_e.slab.get(index)?),
+        Kind::Load => Item::Load(rab_e.load.get(index)?),
+        Kind::Partition => Item::Partition(rab_e.part.get(index)?),
+        Kind::Pile => Item::Pile(rab_e.pile.get(index)?),
+        Kind::FSlab => Item::FSlab(rab_e.f_slab.get(index)?),
+        Kind::FBeam => Item::FBeam(rab_e.f_beam.get(index)?),
+    })
+}
+
+impl Building {
+    /// Все элементы здания: этажи снизу вверх, на этаже - по типам в порядке Kind::ALL
+    pub fn elements(&self) -> impl Iterator<Item = Element<'_>> + '_ {
+        self.storeys().into_iter().flat_map(|storey| {
+            let (storey, rab_e) = (storey.index, storey.rab_e);
+            Kind::ALL.iter().flat_map(move |&kind| {
+                (0..kind.len(rab_e)).filter_map(move |index| {
+                    Some(Element {
+                        id: ElementId(storey, kind, index),
+                        item: item(rab_e, kind, index)?,
+                        rab_e,
+                    })
+                })
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::rab_e_sig_test::{rab_e_test_sig, rab_e_test_sig_full, read_test_building};
+
+    #[test]
+    fn elements_ids() {
+        let building = read_test_building(&[
+            rab_e_test_sig(2, 3.0, &[], &["test_sig/walls/wall.test"]),
+            rab_e_test_sig(
+                1,
+                3.0,
+                &["test_sig/columns/column_box.test"],
+                &["test_sig/walls/wall.test", "test_sig/walls/wall_2mat.test"],
+            ),
+        ]);
+        let ids: Vec<ElementId> = building.elements().map(|e| e.id).collect();
+        assert_eq!(
+            ids,
+            vec![
+                ElementId(0, Kind::Column, 0),
+                ElementId(0, Kind::Wall, 0),
+                ElementId(0, Kind::Wall, 1),
+                ElementId(1, Kind::Wall, 0),
+            ]
+        );
+        let wall = building
+            .elements()
+            .find(|e| e.id == ElementId(0, Kind::Wall, 1))
+            .expect("no element");
+        assert_eq!(wall.kind(), Kind::Wall);
+        assert_eq!(wall.storey(), 0);
+        assert_eq!(wall.index(), 1);
+        assert!(wall.mat().is_some());
+    }
+    #[test]
+    fn elements_footprint() {
+        let building = read_test_building(&[rab_e_test_sig(
+            1,
+            3.0,
+            &["test_sig/columns/column_box.test"],
+            &["test_sig/walls/wall.test"],
+        )]);
+        let elements: Vec<Element> = building.elements().collect();
+        let p = match elements[0].footprint() {
+            Footprint::Point(p) => p,
+            other => panic!("column footprint: {:?}", other),
+        };
+        let bbox = elements[0].bbox().expect("no bbox");
+        assert_eq!(bbox.min, p);
+        assert_eq!(bbox.max, p);
+        let (p1, p2) = match elements[1].footprint() {
+            Footprint::Line(p1, p2) => (p1, p2),
+            other => panic!("wall footprint: {:?}", other),
+        };
+        let bbox = elements[1].bbox().expect("no bbox");
+        assert_eq!(bbox.min.x, p1.x.min(p2.x));
+        assert_eq!(bbox.max.y, p1.y.max(p2.y));
+    }
+    #[test]
+    fn elements_all_kinds() {
+        let building = read_test_building(&[rab_e_test_sig_full(1, 3.0)]);
+        for kind in Kind::ALL.iter() {
+            let count = building.elements().filter(|e| e.kind() == *kind).count();
+            assert!(count > 0, "{}", kind);
+        }
+        assert_eq!(
+            building.elements().filter(|e| e.mat().is_none()).count(),
+            building.rab_e[0].pile.len() + building.rab_e[0].load.len()
+        );
+    }
+}
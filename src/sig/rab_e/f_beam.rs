@@ -7,9 +7,10 @@ use nom::{
     number::complete::{le_u16, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct FBeam {
     p1: Point, //Координаты 1 точки
     p2: Point, //Координаты 2 точки
@@ -17,7 +18,11 @@ pub struct FBeam {
     mat: u16,     //Номер материала балки
     type_sec: u8, //Тип сечения балки
     //40b
-    sec: Sec,    //Сечение балки
+    sec: Sec, //Сечение балки
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 42>"
+    )]
     ws: Vec<u8>, //42b
 }
 impl HasWrite for FBeam {
@@ -45,6 +50,28 @@ impl fmt::Display for FBeam {
         )
     }
 }
+impl FBeam {
+    /// Образцы по одному на каждый тип сечения
+    pub(crate) fn samples() -> Vec<FBeam> {
+        Sec::samples()
+            .into_iter()
+            .map(|sec| FBeam {
+                sec,
+                ..Default::default()
+            })
+            .collect()
+    }
+    pub fn get_start_point(&self) -> &Point {
+        &self.p1
+    }
+    pub fn get_end_point(&self) -> &Point {
+        &self.p2
+    }
+    pub fn get_mat(&self) -> u16 {
+        self.mat
+    }
+}
+
 pub fn read_fbeam(i: &[u8]) -> IResult<&[u8], FBeam> {
     let (i, p1) = read_point(i)?;
     let (i, p2) = read_point(i)?;
@@ -5,15 +5,32 @@ use nom::{
     number::complete::{le_f32, le_u16, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::ops::Range;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 enum FSlabType {
     NaturalPreset(NaturalPreset),
     NaturalComp(NaturalComp),
     PilingField(PilingField),
     PilingAsNatural(PilingAsNatural),
 }
+impl Default for FSlabType {
+    fn default() -> Self {
+        FSlabType::NaturalPreset(Default::default())
+    }
+}
+impl FSlabType {
+    fn samples() -> Vec<FSlabType> {
+        vec![
+            FSlabType::NaturalPreset(Default::default()),
+            FSlabType::NaturalComp(Default::default()),
+            FSlabType::PilingField(Default::default()),
+            FSlabType::PilingAsNatural(Default::default()),
+        ]
+    }
+}
 impl HasWrite for FSlabType {
     fn write(&self) -> Vec<u8> {
         let mut out: Vec<u8> = vec![];
@@ -39,11 +56,15 @@ impl fmt::Display for FSlabType {
         }
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct NaturalPreset {
     c1: f32, //Жесткость С1
     c2: f32, //Жесткость С2
     //8b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 8>"
+    )]
     ws: Vec<u8>, //8b
 }
 impl HasWrite for NaturalPreset {
@@ -63,9 +84,13 @@ impl fmt::Display for NaturalPreset {
         write!(f, "c1: {}, c2: {}", &self.c1, &self.c2)
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct NaturalComp {
     //20b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 20>"
+    )]
     ws: Vec<u8>, //20b
 }
 impl HasWrite for NaturalComp {
@@ -83,9 +108,13 @@ impl fmt::Display for NaturalComp {
         write!(f, "-20-")
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct PilingField {
     //8b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 8>"
+    )]
     ws: Vec<u8>, //8b
 }
 impl HasWrite for PilingField {
@@ -103,13 +132,17 @@ impl fmt::Display for PilingField {
         write!(f, "-8-")
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct PilingAsNatural {
     step_x: f32,  //Шаг по оси X, м
     step_y: f32,  //Шаг по оси Y, м
     f: f32,       //Несущая способность сваи, тс
     delta_l: f32, //Перемещение при действии силы f, м
     //8b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 8>"
+    )]
     ws: Vec<u8>, //8b
 }
 impl HasWrite for PilingAsNatural {
@@ -135,7 +168,7 @@ impl fmt::Display for PilingAsNatural {
         )
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct FSlab {
     //1b
     bf: u8,         //bF 0=нет, 8=есть
@@ -167,7 +200,11 @@ pub struct FSlab {
     cons_3: u8, //Всегда 1
     //72b
     base: FSlabType, //Тип основания фундаментной плиты. Зависит от type_base
-    ws: Vec<u8>,     //95b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 95>"
+    )]
+    ws: Vec<u8>, //95b
 }
 impl HasWrite for FSlab {
     fn write(&self) -> Vec<u8> {
@@ -235,6 +272,29 @@ impl fmt::Display for FSlab {
     }
 }
 
+impl FSlab {
+    /// Образцы по одному на каждый тип основания
+    pub(crate) fn samples() -> Vec<FSlab> {
+        FSlabType::samples()
+            .into_iter()
+            .map(|base| FSlab {
+                base,
+                ..Default::default()
+            })
+            .collect()
+    }
+    pub fn get_b(&self) -> f32 {
+        self.b
+    }
+    pub fn get_mat(&self) -> u16 {
+        self.mat
+    }
+    /// Номера полилиний фундаментной плиты в rab_e.poly
+    pub fn get_polys(&self) -> Range<usize> {
+        self.poly_from as usize..self.poly_from as usize + self.poly_num as usize
+    }
+}
+
 pub fn read_fslab(i: &[u8]) -> IResult<&[u8], FSlab> {
     let (i, ws1) = take(1u8)(i)?;
     let (i, bf) = le_u8(i)?;
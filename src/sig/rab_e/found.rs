@@ -1,14 +1,19 @@
 //! Фундамент под стенами и колоннами
 use crate::sig::HasWrite;
 use nom::{bytes::complete::take, number::complete::le_f32, IResult};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Found {
     b: f32, //Ширина, см. -100 для 2 стенки расчет
     l: f32, //Длина, см. Округляется до целого при расчете
     h: f32, //Высота, см. 0 для колонн
     //12b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 12>"
+    )]
     ws: Vec<u8>, //12b
 }
 impl HasWrite for Found {
@@ -30,6 +35,13 @@ impl fmt::Display for Found {
     }
 }
 
+impl Found {
+    /// Ширина, длина, высота, см
+    pub fn get_size(&self) -> (f32, f32, f32) {
+        (self.b, self.l, self.h)
+    }
+}
+
 pub fn read_found(i: &[u8]) -> IResult<&[u8], Found> {
     let (i, b) = le_f32(i)?;
     let (i, l) = le_f32(i)?;
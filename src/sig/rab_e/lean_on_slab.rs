@@ -6,9 +6,10 @@ use nom::{
     number::complete::{le_f32, le_u16, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct LeanOnSlab {
     load_time: u8, //Тип вертикальной нагрузки на плиту. 0=постоянная, 1=длительная, 3=кратковременная
     element_type: u16, //Тип элемента, опирающегося на плиту. 1=колонна, 2=стена
@@ -17,6 +18,10 @@ pub struct LeanOnSlab {
     load_p2: f32,  //Нагрузка во второй точке
     p2: Point,     //Координаты второй точки элемента
     //20 WS
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 20>"
+    )]
     ws: Vec<u8>, //20b
 }
 impl HasWrite for LeanOnSlab {
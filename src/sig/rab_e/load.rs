@@ -2,9 +2,11 @@
 use crate::sig::HasWrite;
 use nom::number::complete::{le_f32, le_u16, le_u8};
 use nom::{bytes::complete::take, IResult};
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::ops::Range;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Load {
     load_time: u16, //Длительность загружения. 0=постоянное, 1=длительное, 2=кратковременное
     load_type: u16, //Вид нагрузки. 1=точечная, 2=линейная, 3=штамп
@@ -20,6 +22,10 @@ pub struct Load {
     //1b
     level: u8, //Уровень приложения нагрузки. 0=плит, 1=фундаментных плит
     //7b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 9>"
+    )]
     ws: Vec<u8>, //9b
 }
 impl HasWrite for Load {
@@ -53,6 +59,13 @@ impl fmt::Display for Load {
     }
 }
 
+impl Load {
+    /// Номера узлов нагрузки в rab_e.node
+    pub fn get_nodes(&self) -> Range<usize> {
+        self.node_from as usize..self.node_from as usize + self.node_num as usize
+    }
+}
+
 pub fn read_load(i: &[u8]) -> IResult<&[u8], Load> {
     let (i, load_time) = le_u16(i)?;
     let (i, load_type) = le_u16(i)?;
@@ -22,14 +22,16 @@ mod unification_slab;
 mod unification_wall_slit;
 mod wall;
 
+pub mod element;
 pub mod rab_e;
 pub mod rab_e_raw;
 
 use crate::sig::HasWrite;
 use nom::{number::complete::le_f32, IResult};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Point {
     pub(crate) x: f32, //Координата, м
     pub(crate) y: f32, //Координата, м
@@ -2,14 +2,19 @@
 use crate::sig::rab_e::*;
 use crate::sig::HasWrite;
 use nom::{bytes::complete::take, number::complete::le_i16, IResult};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Node {
     p: Point,       //Координаты узла
     node_prev: i16, //номер предыдущего узла в полилинии. -1=этот узел первый
     node_next: i16, //номер следующего узла в полилинии. -1=этот узел последний
     //10b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 10>"
+    )]
     ws: Vec<u8>, //10b
 }
 impl HasWrite for Node {
@@ -35,6 +40,12 @@ impl fmt::Display for Node {
     }
 }
 
+impl Node {
+    pub fn get_point(&self) -> &Point {
+        &self.p
+    }
+}
+
 pub fn read_node(i: &[u8]) -> IResult<&[u8], Node> {
     let (i, p) = read_point(i)?;
     let (i, node_prev) = le_i16(i)?;
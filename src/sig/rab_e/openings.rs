@@ -4,9 +4,10 @@ use nom::{
     number::complete::{le_f32, le_u16},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 // Поля публичные, добавить интерфейс
 pub struct Opening {
     pub num_points: u16, //Количество точек отверстия
@@ -35,6 +36,31 @@ impl fmt::Display for Opening {
     }
 }
 
+impl Opening {
+    /// Площадь отверстия по контуру, м2
+    pub fn area(&self) -> f32 {
+        let n = self.x_vec.len().min(self.y_vec.len());
+        let mut sum = 0f32;
+        for i in 0..n {
+            let j = (i + 1) % n;
+            sum += self.x_vec[i] * self.y_vec[j] - self.x_vec[j] * self.y_vec[i];
+        }
+        sum.abs() / 2.0
+    }
+    /// Пересчет числа точек, координат x и y должно быть поровну
+    pub(crate) fn recount(&mut self) -> Result<(), String> {
+        if self.x_vec.len() != self.y_vec.len() {
+            return Err(format!(
+                "opening x_vec.len {} != y_vec.len {}",
+                self.x_vec.len(),
+                self.y_vec.len()
+            ));
+        }
+        self.num_points = crate::sig::count_num("opening points", self.x_vec.len())?;
+        Ok(())
+    }
+}
+
 pub(crate) fn read_op(i: &[u8]) -> IResult<&[u8], Opening> {
     let (i, num_points) = le_u16(i)?;
     let (i, x_vec) = count(le_f32, num_points as usize)(i)?;
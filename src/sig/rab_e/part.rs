@@ -8,9 +8,10 @@ use nom::{
     number::complete::{le_f32, le_u16, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Partition {
     p1: Point, //1-я точка перегородки
     p2: Point, //2-я точка перегородки
@@ -25,7 +26,11 @@ pub struct Partition {
     em_etazh: u8, //Появляется после этажа N
     //17b
     op: Vec<Opening>, //Вектор отверстий
-    ws: Vec<u8>,      //26b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 26>"
+    )]
+    ws: Vec<u8>, //26b
 }
 impl HasWrite for Partition {
     fn write(&self) -> Vec<u8> {
@@ -66,6 +71,33 @@ impl fmt::Display for Partition {
     }
 }
 
+impl Partition {
+    /// Образец с одним отверстием
+    pub(crate) fn samples() -> Vec<Partition> {
+        vec![Partition {
+            op: vec![Default::default()],
+            ..Default::default()
+        }]
+    }
+    pub fn get_start_point(&self) -> &Point {
+        &self.p1
+    }
+    pub fn get_end_point(&self) -> &Point {
+        &self.p2
+    }
+    pub fn get_mat(&self) -> u16 {
+        self.mat
+    }
+    /// Пересчет счетчика отверстий
+    pub(crate) fn recount(&mut self) -> Result<(), String> {
+        for op in self.op.iter_mut() {
+            op.recount()?;
+        }
+        self.op_num = crate::sig::count_num("partition openings", self.op.len())?;
+        Ok(())
+    }
+}
+
 pub fn read_part(i: &[u8]) -> IResult<&[u8], Partition> {
     let (i, p1) = read_point(i)?;
     let (i, p2) = read_point(i)?;
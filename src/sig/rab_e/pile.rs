@@ -6,14 +6,29 @@ use nom::{
     number::complete::{le_f32, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 enum PileType {
     EF(PileEF),
     FL(PileFL),
     Size(PileSize),
 }
+impl Default for PileType {
+    fn default() -> Self {
+        PileType::EF(Default::default())
+    }
+}
+impl PileType {
+    fn samples() -> Vec<PileType> {
+        vec![
+            PileType::EF(Default::default()),
+            PileType::FL(Default::default()),
+            PileType::Size(Default::default()),
+        ]
+    }
+}
 impl HasWrite for PileType {
     fn write(&self) -> Vec<u8> {
         let mut out = vec![];
@@ -37,10 +52,14 @@ impl fmt::Display for PileType {
         }
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct PileEF {
     ef: f32, //Жесткость сваи, тс
     //2b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 2>"
+    )]
     ws: Vec<u8>, //2b
 }
 impl HasWrite for PileEF {
@@ -59,11 +78,15 @@ impl fmt::Display for PileEF {
         write!(f, "EF: {}", &self.ef)
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct PileFL {
     f: f32,       //Нагрузка на сваю, тс
     delta_l: f32, //Перемещение при нагрузке, м
     //2b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 2>"
+    )]
     ws: Vec<u8>, //2b
 }
 impl HasWrite for PileFL {
@@ -83,7 +106,7 @@ impl fmt::Display for PileFL {
         write!(f, "f: {}, delta L: {}", &self.f, &self.delta_l)
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct PileSize {
     sec: u8,      //Сечение. 0=прямоугольник, 3=круг
     l: f32,       //Длина сваи, см
@@ -94,6 +117,10 @@ pub struct PileSize {
     b_d: f32, //Ширина/диаметр, см
     h_t: f32, //Высота/толщина, см
     //2b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 11>"
+    )]
     ws: Vec<u8>, //11b
 }
 impl HasWrite for PileSize {
@@ -123,14 +150,18 @@ impl fmt::Display for PileSize {
         )
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Pile {
     //2b
     p: Point,      //Точка сваи
     pile_type: u8, //Тип сваи: жесткость/несущая способность/габариты
     //15b
     base: PileType, //Перечисление типов
-    ws: Vec<u8>,    //17b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 17>"
+    )]
+    ws: Vec<u8>, //17b
 }
 impl HasWrite for Pile {
     fn write(&self) -> Vec<u8> {
@@ -156,6 +187,38 @@ impl fmt::Display for Pile {
     }
 }
 
+impl Pile {
+    /// Образцы по одному на каждый тип сваи
+    pub(crate) fn samples() -> Vec<Pile> {
+        PileType::samples()
+            .into_iter()
+            .map(|base| Pile {
+                base,
+                ..Default::default()
+            })
+            .collect()
+    }
+    pub fn get_point(&self) -> &Point {
+        &self.p
+    }
+    /// Тип сваи: EF, F-L, size
+    pub fn kind(&self) -> &str {
+        match &self.base {
+            PileType::EF(_) => "EF",
+            PileType::FL(_) => "F-L",
+            PileType::Size(_) => "size",
+        }
+    }
+    /// Параметры сваи текстом
+    pub fn params(&self) -> String {
+        match &self.base {
+            PileType::EF(r) => r.to_string(),
+            PileType::FL(r) => r.to_string(),
+            PileType::Size(r) => r.to_string(),
+        }
+    }
+}
+
 pub fn read_pile_ef(i: &[u8]) -> IResult<&[u8], PileEF> {
     let (i, ef) = le_f32(i)?;
     let (i, ws1) = take(2u8)(i)?;
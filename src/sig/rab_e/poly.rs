@@ -5,9 +5,11 @@ use nom::{
     number::complete::{le_i16, le_u16, le_u32, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::ops::Range;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Poly {
     poly_type: u16, //тип полилинии 0=контур элемента, 16=отверстие
     node_from: u16, //С узла N
@@ -18,6 +20,10 @@ pub struct Poly {
     sig_type: u8,   //Тип конструкривного элемента, который образует полилиния
     sig_num: u32,   //N конструкривного элемента, который образует полилиния (u64?)
     //6b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 6>"
+    )]
     ws: Vec<u8>, //6b
 }
 impl HasWrite for Poly {
@@ -53,6 +59,17 @@ impl fmt::Display for Poly {
     }
 }
 
+impl Poly {
+    /// Номера узлов полилинии в rab_e.node
+    pub fn get_nodes(&self) -> Range<usize> {
+        self.node_from as usize..self.node_from as usize + self.node_num as usize
+    }
+    /// Контур элемента (не отверстие)
+    pub fn is_contour(&self) -> bool {
+        self.poly_type == 0
+    }
+}
+
 pub fn read_poly(i: &[u8]) -> IResult<&[u8], Poly> {
     let (i, poly_type) = le_u16(i)?;
     let (i, node_from) = le_u16(i)?;
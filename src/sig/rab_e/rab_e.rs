@@ -6,7 +6,9 @@ use nom::{
     number::complete::{le_f32, le_u16, le_u64, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::ops::Range;
 use std::str;
 
 use crate::sig::rab_e::beam::read_beam;
@@ -28,9 +30,11 @@ use crate::sig::rab_e::unification_slab::read_unification_slab;
 use crate::sig::rab_e::unification_wall_slit::read_unification_wall_slit;
 use crate::sig::rab_e::wall::read_wall;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct RabE {
+    #[serde(with = "crate::sig::hex")]
     pub name: [u8; 7],
+    #[serde(with = "crate::sig::hex")]
     pub flag_line: [u8; 6],
     pub head: HeadEtazh,
     pub column: Vec<rab_e::column::Column>,
@@ -164,6 +168,132 @@ impl HasWrite for RabE {
         }
     }
 }
+impl RabE {
+    /// Этаж с образцами всех видов элементов: по одному элементу на группу,
+    /// у элементов с вариантами и вложенными массивами - по образцу на вариант
+    pub(crate) fn sample() -> RabE {
+        fn one<T: Default>() -> Vec<T> {
+            vec![T::default()]
+        }
+        RabE {
+            column: rab_e::column::Column::samples(),
+            wall: rab_e::wall::Wall::samples(),
+            beam: rab_e::beam::Beam::samples(),
+            slab: one(),
+            load: one(),
+            poly: one(),
+            node: one(),
+            sig_1: one(),
+            sig_2: one(),
+            sig_3: one(),
+            sig_4: one(),
+            diagram_force: one(),
+            diagram: one(),
+            f_wall: one(),
+            part: rab_e::part::Partition::samples(),
+            sig_5: one(),
+            lean_on_slab: one(),
+            diagram_wind_force: one(),
+            unification_slab: one(),
+            f_slab: rab_e::f_slab::FSlab::samples(),
+            diagram_unc: one(),
+            unification_found: rab_e::unification_found::UnificationFound::samples(),
+            pile: rab_e::pile::Pile::samples(),
+            unification_wall_slits: one(),
+            unification_fslab: one(),
+            f_beam: rab_e::f_beam::FBeam::samples(),
+            ..Default::default()
+        }
+    }
+    /// Пересчет счетчиков по длинам векторов
+    ///
+    /// Для здания из JSON или текста: после удаления или добавления элементов счетчики
+    /// заголовка и элементов соответствуют векторам. Ссылки плит на полилинии и
+    /// полилиний и нагрузок на узлы должны остаться в пределах векторов.
+    pub fn recount(&mut self) -> Result<(), String> {
+        let polys = self.poly.len();
+        for (n, i) in self.slab.iter().enumerate() {
+            check_range("slab", n, "poly", i.get_polys(), polys)?;
+        }
+        for (n, i) in self.f_slab.iter().enumerate() {
+            check_range("f_slab", n, "poly", i.get_polys(), polys)?;
+        }
+        let nodes = self.node.len();
+        for (n, i) in self.poly.iter().enumerate() {
+            check_range("poly", n, "node", i.get_nodes(), nodes)?;
+        }
+        for (n, i) in self.load.iter().enumerate() {
+            check_range("load", n, "node", i.get_nodes(), nodes)?;
+        }
+        for i in self.wall.iter_mut() {
+            i.recount()?;
+        }
+        for i in self.part.iter_mut() {
+            i.recount()?;
+        }
+        for i in self.slab.iter() {
+            i.check()?;
+        }
+        for i in self.unification_slab.iter_mut() {
+            i.recount()?;
+        }
+        for i in self.unification_found.iter_mut() {
+            i.recount()?;
+        }
+        for i in self.unification_wall_slits.iter_mut() {
+            i.recount()?;
+        }
+        for i in self.unification_fslab.iter_mut() {
+            i.recount()?;
+        }
+        let head = &mut self.head;
+        head.columns_num = count_num("column", self.column.len())?;
+        head.walls_num = count_num("wall", self.wall.len())?;
+        head.beams_num = count_num("beam", self.beam.len())?;
+        head.slabs_num = count_num("slab", self.slab.len())?;
+        head.loads_num = count_num("load", self.load.len())?;
+        head.poly_num = count_num("poly", self.poly.len())?;
+        head.nodes_num = count_num("node", self.node.len())?;
+        head.sig_1_num = count_num("sig_1", self.sig_1.len())?;
+        head.sig_2_num = count_num("sig_2", self.sig_2.len())?;
+        head.sig_3_num = count_num("sig_3", self.sig_3.len())?;
+        head.sig_4_num = count_num("sig_4", self.sig_4.len())?;
+        head.diagrams_force_num = count_num("diagram_force", self.diagram_force.len())?;
+        head.diagrams_num = count_num("diagram", self.diagram.len())?;
+        head.fwalls_num = count_num("f_wall", self.f_wall.len())?;
+        head.parts_num = count_num("part", self.part.len())?;
+        head.sig_5_num = count_num("sig_5", self.sig_5.len())?;
+        head.leans_on_slab_num = count_num("lean_on_slab", self.lean_on_slab.len())?;
+        head.diagrams_wind_force_num =
+            count_num("diagram_wind_force", self.diagram_wind_force.len())?;
+        head.unification_slabs_num = count_num("unification_slab", self.unification_slab.len())?;
+        head.fslabs_num = count_num("f_slab", self.f_slab.len())?;
+        head.diagrams_unc_num = count_num("diagram_unc", self.diagram_unc.len())?;
+        head.unification_founds_num = count_num("unification_found", self.unification_found.len())?;
+        head.piles_num = count_num("pile", self.pile.len())?;
+        head.unification_wall_slits_num =
+            count_num("unification_wall_slits", self.unification_wall_slits.len())?;
+        head.unification_fslabs_num = count_num("unification_fslab", self.unification_fslab.len())?;
+        head.fbeams_num = count_num("f_beam", self.f_beam.len())?;
+        Ok(())
+    }
+}
+/// Ссылка элемента на номера from..to другого вектора длиной len. Пустая ссылка не проверяется
+fn check_range(
+    kind: &str,
+    index: usize,
+    target: &str,
+    range: Range<usize>,
+    len: usize,
+) -> Result<(), String> {
+    if range.is_empty() || range.end <= len {
+        return Ok(());
+    }
+    Err(format!(
+        "{} {}: {} {}..{} out of {}",
+        kind, index, target, range.start, range.end, len
+    ))
+}
 impl fmt::Display for RabE {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}; flag_line: [", &self.name())?;
@@ -224,19 +354,22 @@ impl fmt::Display for RabE {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct HeadEtazh {
     pub etazh_num: u16,
-    etazh_h: f32,
+    pub etazh_h: f32, //Высота этажа, м
     num1: u16,
     num2: u16,
+    #[serde(with = "crate::sig::hex")]
     ws1_1: [u8; 17],
     xm1: f32, //центр тяжести х
     ym1: f32, // центр тяжести у
     xm2: f32,
     ym2: f32,
+    #[serde(with = "crate::sig::hex")]
     c_sum: [u8; 4], //контрольная сумма?
     slab_alignment: u16,
+    #[serde(with = "crate::sig::hex")]
     ws1_2: [u8; 13],
     columns_num: u16,
     walls_num: u16,
@@ -264,6 +397,10 @@ pub struct HeadEtazh {
     unification_wall_slits_num: u16,
     unification_fslabs_num: u16,
     fbeams_num: u16,
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 180>"
+    )]
     ws6: Vec<u8>, //180
 }
 impl HasWrite for HeadEtazh {
@@ -377,7 +514,7 @@ pub fn read_rab_e(i: &[u8]) -> IResult<&[u8], Vec<RabE>> {
     let (i, rab_e_etazh) = many1(read_rab_e_etazh)(i)?;
     Ok((i, rab_e_etazh))
 }
-fn read_rab_e_etazh(i: &[u8]) -> IResult<&[u8], RabE> {
+pub(crate) fn read_rab_e_etazh(i: &[u8]) -> IResult<&[u8], RabE> {
     let (i, _) = tag("rab.e")(i)?;
     let (i, num1) = le_u8(i)?;
     let (i, num2) = le_u8(i)?;
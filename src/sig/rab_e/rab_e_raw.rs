@@ -63,7 +63,7 @@ pub fn read_rab_e_raw(i: &[u8]) -> IResult<&[u8], Vec<RabERaw>> {
     let (i, etazh) = many1(read_etazh)(i)?;
     Ok((i, etazh))
 }
-fn read_etazh(i: &[u8]) -> IResult<&[u8], RabERaw> {
+pub(crate) fn read_etazh(i: &[u8]) -> IResult<&[u8], RabERaw> {
     let (i, _) = tag("rab.e")(i)?;
     let (i, num1) = le_u8(i)?;
     let (i, num2) = le_u8(i)?;
@@ -5,9 +5,10 @@ use nom::{
     number::complete::{le_f32, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum Sec {
     Rectangle(RectangleSec),
     Circle(CircleSec),
@@ -17,6 +18,11 @@ pub enum Sec {
     ISec(ISec),
     Shelves(ShelvesSec),
 }
+impl Default for Sec {
+    fn default() -> Self {
+        Sec::Rectangle(Default::default())
+    }
+}
 impl HasWrite for Sec {
     fn write(&self) -> Vec<u8> {
         let mut out = vec![];
@@ -48,11 +54,74 @@ impl fmt::Display for Sec {
         }
     }
 }
-#[derive(Debug)]
+impl Sec {
+    /// Все варианты сечения с нулевыми размерами
+    pub(crate) fn samples() -> Vec<Sec> {
+        vec![
+            Sec::Rectangle(Default::default()),
+            Sec::Circle(Default::default()),
+            Sec::Cross(Default::default()),
+            Sec::Ring(Default::default()),
+            Sec::Box(Default::default()),
+            Sec::ISec(Default::default()),
+            Sec::Shelves(Default::default()),
+        ]
+    }
+    /// Тип сечения
+    pub fn kind(&self) -> &str {
+        match &self {
+            Sec::Rectangle(_) => "rectangle",
+            Sec::Circle(_) => "circle",
+            Sec::Cross(_) => "cross",
+            Sec::Ring(_) => "ring",
+            Sec::Box(_) => "box",
+            Sec::ISec(_) => "i_sec",
+            Sec::Shelves(_) => "shelves",
+        }
+    }
+    /// Размеры сечения, см: имя и значение
+    pub fn dims(&self) -> Vec<(&'static str, f32)> {
+        match &self {
+            Sec::Rectangle(s) => vec![("b", s.b), ("h", s.h)],
+            Sec::Circle(s) => vec![("d", s.d)],
+            Sec::Cross(s) => vec![
+                ("b1", s.b1),
+                ("b2", s.b2),
+                ("b3", s.b3),
+                ("h1", s.h1),
+                ("h2", s.h2),
+                ("h3", s.h3),
+            ],
+            Sec::Ring(s) => vec![("d", s.d), ("t", s.t)],
+            Sec::Box(s) => vec![("b", s.b), ("b1", s.b1), ("h", s.h), ("h1", s.h1)],
+            Sec::ISec(s) => vec![
+                ("b", s.b),
+                ("b1", s.b1),
+                ("b2", s.b2),
+                ("h", s.h),
+                ("h1", s.h1),
+                ("h2", s.h2),
+            ],
+            Sec::Shelves(s) => vec![
+                ("b", s.b),
+                ("h", s.h),
+                ("b1", s.b1),
+                ("h1", s.h1),
+                ("b2", s.b2),
+                ("h2", s.h2),
+            ],
+        }
+    }
+}
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct RectangleSec {
     b: f32,
     h: f32,
-    flag_f: u8,  //Флаг подбора сечения. 0=нет, 1=подбор h, 2=подбор b, 3=подбор h и b
+    flag_f: u8, //Флаг подбора сечения. 0=нет, 1=подбор h, 2=подбор b, 3=подбор h и b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 2>"
+    )]
     ws: Vec<u8>, //2b
 }
 impl HasWrite for RectangleSec {
@@ -73,10 +142,14 @@ impl fmt::Display for RectangleSec {
         write!(f, "b: {}, h: {}", &self.b, &self.h)
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct CircleSec {
     d: f32,
-    flag_f: u8,  //Флаг подбора сечения. 0=нет, 1=подбор
+    flag_f: u8, //Флаг подбора сечения. 0=нет, 1=подбор
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 2>"
+    )]
     ws: Vec<u8>, //2b
 }
 impl HasWrite for CircleSec {
@@ -96,7 +169,7 @@ impl fmt::Display for CircleSec {
         write!(f, "d: {}", &self.d)
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct CrossSec {
     b1: f32,
     b2: f32,
@@ -104,6 +177,10 @@ pub struct CrossSec {
     h1: f32,
     h2: f32,
     h3: f32,
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 2>"
+    )]
     ws: Vec<u8>, //2b
 }
 impl HasWrite for CrossSec {
@@ -131,10 +208,14 @@ impl fmt::Display for CrossSec {
         )
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct RingSec {
     d: f32,
     t: f32,
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 2>"
+    )]
     ws: Vec<u8>, //2b
 }
 impl HasWrite for RingSec {
@@ -154,12 +235,16 @@ impl fmt::Display for RingSec {
         write!(f, "d: {}, t: {}", &self.d, &self.t)
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct BoxSec {
     b: f32,
     b1: f32,
     h: f32,
     h1: f32,
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 2>"
+    )]
     ws: Vec<u8>, //2b
 }
 impl HasWrite for BoxSec {
@@ -185,7 +270,7 @@ impl fmt::Display for BoxSec {
         )
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ISec {
     b: f32,
     b1: f32,
@@ -193,6 +278,10 @@ pub struct ISec {
     h: f32,
     h1: f32,
     h2: f32,
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 2>"
+    )]
     ws: Vec<u8>, //2b
 }
 impl HasWrite for ISec {
@@ -220,7 +309,7 @@ impl fmt::Display for ISec {
         )
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ShelvesSec {
     b: f32,
     h: f32,
@@ -228,6 +317,10 @@ pub struct ShelvesSec {
     h1: f32,
     b2: f32,
     h2: f32,
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 2>"
+    )]
     ws: Vec<u8>, //2b
 }
 impl HasWrite for ShelvesSec {
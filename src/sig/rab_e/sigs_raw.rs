@@ -2,10 +2,15 @@
 //!Хранение в виде вектора байт фиксированного размера
 use crate::sig::HasWrite;
 use nom::{bytes::complete::take, IResult};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Sig1 {
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 32>"
+    )]
     source: Vec<u8>, //32b
 }
 impl HasWrite for Sig1 {
@@ -25,8 +30,12 @@ impl fmt::Display for Sig1 {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Sig2 {
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 32>"
+    )]
     source: Vec<u8>, //32b
 }
 impl HasWrite for Sig2 {
@@ -46,8 +55,12 @@ impl fmt::Display for Sig2 {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Sig3 {
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 23>"
+    )]
     source: Vec<u8>, //23b
 }
 impl HasWrite for Sig3 {
@@ -67,8 +80,12 @@ impl fmt::Display for Sig3 {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Sig4 {
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 11>"
+    )]
     source: Vec<u8>, //11b
 }
 impl HasWrite for Sig4 {
@@ -88,8 +105,12 @@ impl fmt::Display for Sig4 {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Sig5 {
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 22>"
+    )]
     source: Vec<u8>, //22b
 }
 impl HasWrite for Sig5 {
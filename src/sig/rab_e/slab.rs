@@ -6,10 +6,12 @@ use nom::{
     number::complete::{le_f32, le_u16, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
 use std::fmt;
+use std::ops::Range;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Slab {
     //1b
     bf: u8,    //Флаг bF. 0=нет, 1=есть
@@ -33,7 +35,12 @@ pub struct Slab {
     emerge: u8,   //Появляется после. 0=всего здания, 1=этажа N, 2=своего этажа
     em_etazh: u8, //Появляется после этажа N
     //58b
-    ws: Vec<u8>,       //85b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 85>"
+    )]
+    ws: Vec<u8>, //85b
+    #[serde(with = "crate::sig::hex")]
     load_vec: Vec<u8>, //0b без расчета, 24b расчетб МКЭ
 }
 impl HasWrite for Slab {
@@ -77,6 +84,32 @@ impl fmt::Display for Slab {
         )
     }
 }
+impl Slab {
+    pub fn get_b(&self) -> f32 {
+        self.b
+    }
+    pub fn get_mat(&self) -> u16 {
+        self.mat
+    }
+    /// Номера полилиний плиты в rab_e.poly
+    pub fn get_polys(&self) -> Range<usize> {
+        self.poly_from as usize..self.poly_from as usize + self.poly_num as usize
+    }
+    /// Проверка load_vec: 24b при unc_num = 3, иначе пусто
+    pub(crate) fn check(&self) -> Result<(), String> {
+        let len = if self.unc_num == 3 { 24 } else { 0 };
+        if self.load_vec.len() != len {
+            return Err(format!(
+                "slab load_vec.len {} with unc_num {}, expected {}",
+                self.load_vec.len(),
+                self.unc_num,
+                len
+            ));
+        }
+        Ok(())
+    }
+}
+
 pub fn read_slab(i: &[u8]) -> IResult<&[u8], Slab> {
     let (i, ws1) = take(1u8)(i)?;
     let (i, bf) = le_u8(i)?;
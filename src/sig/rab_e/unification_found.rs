@@ -2,15 +2,20 @@
 use crate::sig::rab_e::*;
 use crate::sig::HasWrite;
 use nom::{bytes::complete::take, multi::count, number::complete::le_u16, IResult};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UnificationFound {
     unification_group: u16, //Номер группы унификаций
     amount: u16,            //Количество элементов в группе унификаций
     //64b WS
     elements: Vec<FoundElem>, //Вектор номеров элементов в группе
-    ws: Vec<u8>,              //64b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 64>"
+    )]
+    ws: Vec<u8>, //64b
 }
 impl HasWrite for UnificationFound {
     fn write(&self) -> Vec<u8> {
@@ -27,6 +32,20 @@ impl HasWrite for UnificationFound {
         ""
     }
 }
+impl UnificationFound {
+    /// Образец с одним элементом в группе
+    pub(crate) fn samples() -> Vec<UnificationFound> {
+        vec![UnificationFound {
+            elements: vec![Default::default()],
+            ..Default::default()
+        }]
+    }
+    /// Пересчет числа элементов в группе
+    pub(crate) fn recount(&mut self) -> Result<(), String> {
+        self.amount = crate::sig::count_num("unification elements", self.elements.len())?;
+        Ok(())
+    }
+}
 impl fmt::Display for UnificationFound {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -42,11 +61,15 @@ impl fmt::Display for UnificationFound {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct FoundElem {
     element_type: u16, //Тип конструкции. 1=колонна, 2=стена
     element_num: u16,  //Номер элемента в схеме
     //16 WS
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 16>"
+    )]
     ws: Vec<u8>, //16b
 }
 impl HasWrite for FoundElem {
@@ -2,15 +2,20 @@
 use crate::sig::rab_e::*;
 use crate::sig::HasWrite;
 use nom::{bytes::complete::take, multi::count, number::complete::le_u16, IResult};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UnificationSlab {
     unification_group: u16, //Номер группы унификаций
     amount: u16,            //Количество элементов в группе унификаций
     //40b WS
     elements: Vec<u16>, //Вектор номеров элементов в группе
-    ws: Vec<u8>,        //40b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 40>"
+    )]
+    ws: Vec<u8>, //40b
 }
 impl HasWrite for UnificationSlab {
     fn write(&self) -> Vec<u8> {
@@ -27,6 +32,13 @@ impl HasWrite for UnificationSlab {
         ""
     }
 }
+impl UnificationSlab {
+    /// Пересчет числа элементов в группе
+    pub(crate) fn recount(&mut self) -> Result<(), String> {
+        self.amount = crate::sig::count_num("unification elements", self.elements.len())?;
+        Ok(())
+    }
+}
 impl fmt::Display for UnificationSlab {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
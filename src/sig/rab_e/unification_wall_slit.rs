@@ -3,16 +3,21 @@ use crate::sig::rab_e::unification_found::read_unification_found;
 use crate::sig::rab_e::*;
 use crate::sig::HasWrite;
 use nom::{bytes::complete::take, multi::count, number::complete::le_u16, IResult};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UnificationWallSlit {
     unification_group: u16, //Номер группы унификаций
     //2b WS
     amount: u16, //Количество элементов в группе унификаций
     //32b WS
     elements: Vec<u16>, //Вектор номеров элементов в группе
-    ws: Vec<u8>,        //34b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 34>"
+    )]
+    ws: Vec<u8>, //34b
 }
 impl HasWrite for UnificationWallSlit {
     fn write(&self) -> Vec<u8> {
@@ -30,6 +35,13 @@ impl HasWrite for UnificationWallSlit {
         ""
     }
 }
+impl UnificationWallSlit {
+    /// Пересчет числа элементов в группе
+    pub(crate) fn recount(&mut self) -> Result<(), String> {
+        self.amount = crate::sig::count_num("unification elements", self.elements.len())?;
+        Ok(())
+    }
+}
 impl fmt::Display for UnificationWallSlit {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -8,9 +8,10 @@ use nom::{
     number::complete::{le_f32, le_i16, le_u16, le_u32, le_u8},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Wall {
     p1: Point,         //1-я точка стены
     p2: Point,         //2-я точка стены
@@ -55,7 +56,11 @@ pub struct Wall {
     mat: u16,       //Номер материала стены
     //9b WS
     op: Vec<Opening>, //Вектор отверстий
-    ws: Vec<u8>,      //17b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 17>"
+    )]
+    ws: Vec<u8>, //17b
 }
 impl HasWrite for Wall {
     fn write(&self) -> Vec<u8> {
@@ -127,12 +132,41 @@ impl fmt::Display for Wall {
 }
 
 impl Wall {
+    /// Образец с одним отверстием
+    pub(crate) fn samples() -> Vec<Wall> {
+        vec![Wall {
+            op: vec![Default::default()],
+            ..Default::default()
+        }]
+    }
     pub fn get_start_point(&self) -> &Point {
         &self.p1
     }
     pub fn get_end_point(&self) -> &Point {
         &self.p2
     }
+    /// Толщина, см
+    pub fn get_b(&self) -> f32 {
+        self.b
+    }
+    pub fn get_mat(&self) -> u16 {
+        self.mat
+    }
+    /// Армирование стены, кг
+    pub fn get_reinforcement(&self) -> f32 {
+        self.reinforcement_wall
+    }
+    pub fn get_openings(&self) -> &[Opening] {
+        &self.op
+    }
+    /// Пересчет счетчика отверстий
+    pub(crate) fn recount(&mut self) -> Result<(), String> {
+        for op in self.op.iter_mut() {
+            op.recount()?;
+        }
+        self.op_num = crate::sig::count_num("wall openings", self.op.len())?;
+        Ok(())
+    }
 }
 
 pub fn read_wall(i: &[u8]) -> IResult<&[u8], Wall> {
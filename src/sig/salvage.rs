@@ -0,0 +1,430 @@
+//! Восстановление поврежденного файла
+//!
+//! Сигнатуры читаются по одной. Нераспознанные или поврежденные данные пропускаются до
+//! следующего известного имени сигнатуры. Этаж rab.e, который не разбирается или
+//! разбирается не на всю длину из заголовка, остается сырым (RabERaw) и записывается
+//! обратно без изменений. Паника парсера на поврежденных данных считается ошибкой
+//! разбора. Все пропуски попадают в отчет о повреждениях.
+use crate::sig::barpbres_fe::read_barpbres_fe;
+use crate::sig::bkngwl_bnw::read_bkngwl_bnw;
+use crate::sig::boknagr_bkn::read_boknagr_bkn;
+use crate::sig::building::Building;
+use crate::sig::clmn_uni::read_clmn_uni;
+use crate::sig::coeffs_rsu::read_coeffs_rsu;
+use crate::sig::elems_fe::read_elems_fe;
+use crate::sig::elemsres_fe::read_elemsres_fe;
+use crate::sig::elsss_fe::read_elsss_fe;
+use crate::sig::etnames_et::read_etnames_et;
+use crate::sig::expert::read_expert;
+use crate::sig::file_type::read_file_type;
+use crate::sig::head_fe::read_head_fe;
+use crate::sig::isoar_fe::read_isoar_fe;
+use crate::sig::loadcomb_cds::read_loadcomb_cds;
+use crate::sig::material_mt::read_material_mt;
+use crate::sig::ndunions_fe::read_ndunions_fe;
+use crate::sig::nodes_fe::read_nodes_fe;
+use crate::sig::nodesres_fe::read_nodesres_fe;
+use crate::sig::object_nam::read_object_nam;
+use crate::sig::pop_cut::read_pop_cut;
+use crate::sig::procalc_set::read_procalc_set;
+use crate::sig::prores_use::read_prores_use;
+use crate::sig::rab_a0::read_rab_a0;
+use crate::sig::rab_e::rab_e::read_rab_e_etazh;
+use crate::sig::rab_e::rab_e_raw::{read_etazh, RabERaw};
+use crate::sig::rab_o0::read_rab_o0;
+use crate::sig::rab_sdr::read_rab_sdr;
+use crate::sig::rab_zag::read_rab_zag;
+use crate::sig::reper_pos::read_reper_pos;
+use crate::sig::rigbodys_fe::read_rigbodys_fe;
+use crate::sig::rigids_fe::read_rigids_fe;
+use crate::sig::rzagnums_fe::read_rzagnums_fe;
+use crate::sig::seism_rsp::read_seism_rsp;
+use crate::sig::slits_slt::read_slits_slt;
+use crate::sig::sltwlexp_grp::read_sltwlexp_grp;
+use crate::sig::szinfo_szi::read_szinfo_szi;
+use crate::sig::vnum_fe::read_vnum_fe;
+use crate::sig::wallascn_uni::read_wallascn_uni;
+use crate::sig::wind_rsp::read_wind_rsp;
+use crate::sig::zagrcmbs_zc::read_zagrcmbs_zc;
+use crate::sig::zagrs_fe::read_zagrs_fe;
+use crate::sig::HasWrite;
+use nom::IResult;
+use std::fmt;
+use std::panic::{self, UnwindSafe};
+
+/// Имена сигнатур, по которым ищется продолжение после повреждения
+const NAMES: [&str; 39] = [
+    "barpbres.fe",
+    "bkngwl.bnw",
+    "boknagr.bkn",
+    "clmn.uni",
+    "coeffs.rsu",
+    "elems.fe",
+    "elemsres.fe",
+    "elsss.fe",
+    "etnames.et",
+    "expert",
+    "head.fe",
+    "isoar.fe",
+    "loadcomb.cds",
+    "material.mt",
+    "ndunions.fe",
+    "nodes.fe",
+    "nodesres.fe",
+    "object.nam",
+    "pop.cut",
+    "procalc.set",
+    "prores.use",
+    "rab.a0",
+    "rab.e",
+    "rab.o0",
+    "rab.sdr",
+    "rab.zag",
+    "reper.pos",
+    "rigbodys.fe",
+    "rigids.fe",
+    "rzagnums.fe",
+    "seism.rsp",
+    "slits.slt",
+    "sltwlexp.grp",
+    "szinfo.szi",
+    "vnum.fe",
+    "wallascn.uni",
+    "wind.rsp",
+    "zagrcmbs.zc",
+    "zagrs.fe",
+];
+
+/// Поврежденный участок файла
+#[derive(Debug, PartialEq)]
+pub struct Damage {
+    pub offset: usize, //Смещение от начала файла
+    pub len: usize,    //Длина участка, байт
+    pub what: String,
+}
+impl fmt::Display for Damage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "offset {}, {} bytes: {}",
+            &self.offset, &self.len, &self.what
+        )
+    }
+}
+
+/// Этаж в порядке файла: разобранный (номер в building.rab_e) или сырой
+#[derive(Debug)]
+enum Storey {
+    Parsed(usize),
+    Raw(RabERaw),
+}
+
+/// Восстановленное здание
+#[derive(Debug)]
+pub struct Salvage {
+    pub building: Building,
+    pub damage: Vec<Damage>,
+    storeys: Vec<Storey>,
+}
+impl Salvage {
+    /// Этажи, оставшиеся сырыми
+    pub fn raw_storeys(&self) -> Vec<&RabERaw> {
+        self.storeys
+            .iter()
+            .filter_map(|s| match s {
+                Storey::Raw(raw) => Some(raw),
+                Storey::Parsed(_) => None,
+            })
+            .collect()
+    }
+}
+impl HasWrite for Salvage {
+    fn write(&self) -> Vec<u8> {
+        let mut rab_e = vec![];
+        for storey in self.storeys.iter() {
+            match storey {
+                Storey::Parsed(n) => rab_e.extend(self.building.rab_e[*n].write()),
+                Storey::Raw(raw) => rab_e.extend(raw.write()),
+            }
+        }
+        self.building.write_with_rab_e(&rab_e)
+    }
+    fn name(&self) -> &str {
+        "BUILDING.chg"
+    }
+}
+impl fmt::Display for Salvage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.damage.is_empty() {
+            writeln!(f, "no damage")?;
+        }
+        for damage in self.damage.iter() {
+            writeln!(f, "{}", damage)?;
+        }
+        let raw: Vec<&str> = self.raw_storeys().iter().map(|r| r.name()).collect();
+        writeln!(
+            f,
+            "storeys: {} parsed, {} raw [{}]",
+            self.building.rab_e.len(),
+            raw.len(),
+            raw.join(", ")
+        )
+    }
+}
+
+/// Разбор без паники: паника парсера - ошибка разбора на входе i
+fn unwind<'a, T>(
+    i: &'a [u8],
+    read: impl FnOnce() -> IResult<&'a [u8], T> + UnwindSafe,
+) -> IResult<&'a [u8], T> {
+    match panic::catch_unwind(read) {
+        Ok(result) => result,
+        Err(_) => Err(nom::Err::Failure((i, nom::error::ErrorKind::Verify))),
+    }
+}
+
+/// Чтение сигнатуры в свободное место здания. true - место уже занято, сигнатура пропущена
+fn put<'a, T>(
+    i: &'a [u8],
+    slot: &mut Option<T>,
+    read: fn(&'a [u8]) -> IResult<&'a [u8], T>,
+) -> IResult<&'a [u8], bool> {
+    let (i, sig) = unwind(i, || read(i))?;
+    if slot.is_some() {
+        return Ok((i, true));
+    }
+    *slot = Some(sig);
+    Ok((i, false))
+}
+
+/// Сигнатура по имени (кроме rab.e)
+fn read_sig<'a>(b: &mut Building, name: &str, i: &'a [u8]) -> IResult<&'a [u8], bool> {
+    match name {
+        "barpbres.fe" => put(i, &mut b.barpbres_fe, read_barpbres_fe),
+        "bkngwl.bnw" => put(i, &mut b.bkngwl_bnw, read_bkngwl_bnw),
+        "boknagr.bkn" => put(i, &mut b.boknagr_bkn, read_boknagr_bkn),
+        "clmn.uni" => put(i, &mut b.clmn_uni, read_clmn_uni),
+        "coeffs.rsu" => put(i, &mut b.coeffs_rsu, read_coeffs_rsu),
+        "elems.fe" => put(i, &mut b.elems_fe, read_elems_fe),
+        "elemsres.fe" => put(i, &mut b.elemsres_fe, read_elemsres_fe),
+        "elsss.fe" => put(i, &mut b.elsss_fe, read_elsss_fe),
+        "etnames.et" => put(i, &mut b.etnames_et, read_etnames_et),
+        "expert" => put(i, &mut b.expert, read_expert),
+        "head.fe" => put(i, &mut b.head_fe, read_head_fe),
+        "isoar.fe" => put(i, &mut b.isoar_fe, read_isoar_fe),
+        "loadcomb.cds" => put(i, &mut b.loadcomb_cds, read_loadcomb_cds),
+        "material.mt" => put(i, &mut b.material_mt, read_material_mt),
+        "ndunions.fe" => put(i, &mut b.ndunions_fe, read_ndunions_fe),
+        "nodes.fe" => put(i, &mut b.nodes_fe, read_nodes_fe),
+        "nodesres.fe" => put(i, &mut b.nodesres_fe, read_nodesres_fe),
+        "object.nam" => put(i, &mut b.object_nam, read_object_nam),
+        "pop.cut" => put(i, &mut b.pop_cut, read_pop_cut),
+        "procalc.set" => put(i, &mut b.procalc_set, read_procalc_set),
+        "prores.use" => put(i, &mut b.prores_use, read_prores_use),
+        "rab.a0" => put(i, &mut b.rab_a0, read_rab_a0),
+        "rab.o0" => put(i, &mut b.rab_o0, read_rab_o0),
+        "rab.sdr" => put(i, &mut b.rab_sdr, read_rab_sdr),
+        "rab.zag" => put(i, &mut b.rab_zag, read_rab_zag),
+        "reper.pos" => put(i, &mut b.reper_pos, read_reper_pos),
+        "rigbodys.fe" => put(i, &mut b.rigbodys_fe, read_rigbodys_fe),
+        "rigids.fe" => put(i, &mut b.rigids_fe, read_rigids_fe),
+        "rzagnums.fe" => put(i, &mut b.rzagnums_fe, read_rzagnums_fe),
+        "seism.rsp" => put(i, &mut b.seism_rsp, read_seism_rsp),
+        "slits.slt" => put(i, &mut b.slits_slt, read_slits_slt),
+        "sltwlexp.grp" => put(i, &mut b.sltwlexp_grp, read_sltwlexp_grp),
+        "szinfo.szi" => put(i, &mut b.szinfo_szi, read_szinfo_szi),
+        "vnum.fe" => put(i, &mut b.vnum_fe, read_vnum_fe),
+        "wallascn.uni" => put(i, &mut b.wallascn_uni, read_wallascn_uni),
+        "wind.rsp" => put(i, &mut b.wind_rsp, read_wind_rsp),
+        "zagrcmbs.zc" => put(i, &mut b.zagrcmbs_zc, read_zagrcmbs_zc),
+        "zagrs.fe" => put(i, &mut b.zagrs_fe, read_zagrs_fe),
+        _ => Err(nom::Err::Error((i, nom::error::ErrorKind::Tag))),
+    }
+}
+
+fn sig_name(i: &[u8]) -> Option<&'static str> {
+    NAMES.iter().find(|n| i.starts_with(n.as_bytes())).copied()
+}
+
+/// Смещение следующего известного имени сигнатуры после from
+fn resync(original_in: &[u8], from: usize) -> usize {
+    (from + 1..original_in.len())
+        .find(|&n| sig_name(&original_in[n..]).is_some())
+        .unwrap_or(original_in.len())
+}
+
+/// Имя этажа rab.e с номером
+fn storey_name(i: &[u8]) -> String {
+    let end = i.len().min(7);
+    String::from_utf8_lossy(&i[..end])
+        .trim_end_matches(|c: char| !c.is_ascii_alphanumeric())
+        .to_string()
+}
+
+/// Разбор поврежденного файла: все, что читается, и отчет о пропусках
+pub fn salvage(original_in: &[u8]) -> Salvage {
+    let mut salvage = Salvage {
+        building: Building::default(),
+        damage: vec![],
+        storeys: vec![],
+    };
+    let mut pos = 0;
+    if original_in.len() >= 10 {
+        if let Ok((i, file_type)) = read_file_type(original_in) {
+            salvage.building.file_type = file_type;
+            pos = original_in.len() - i.len();
+        }
+    }
+    while pos < original_in.len() {
+        let i = &original_in[pos..];
+        let name = match sig_name(i) {
+            None => {
+                let next = resync(original_in, pos);
+                salvage.damage.push(Damage {
+                    offset: pos,
+                    len: next - pos,
+                    what: "unknown data".to_string(),
+                });
+                pos = next;
+                continue;
+            }
+            Some(name) => name,
+        };
+        let (rest, what) = if name == "rab.e" {
+            read_storey(&mut salvage, i)
+        } else {
+            match read_sig(&mut salvage.building, name, i) {
+                Ok((rest, false)) => (Some(rest), None),
+                Ok((rest, true)) => (Some(rest), Some(format!("{}: duplicate, skipped", name))),
+                Err(_) => (None, Some(format!("{}: damaged, skipped", name))),
+            }
+        };
+        let next = match rest {
+            Some(rest) => original_in.len() - rest.len(),
+            None => resync(original_in, pos),
+        };
+        if let Some(what) = what {
+            salvage.damage.push(Damage {
+                offset: pos,
+                len: next - pos,
+                what,
+            });
+        }
+        pos = next;
+    }
+    salvage
+}
+
+/// Этаж rab.e: разобранный, если он читается ровно на длину из заголовка, иначе сырой
+fn read_storey<'a>(salvage: &mut Salvage, i: &'a [u8]) -> (Option<&'a [u8]>, Option<String>) {
+    let name = storey_name(i);
+    let raw = unwind(i, || read_etazh(i));
+    let parsed = unwind(i, || read_rab_e_etazh(i));
+    match (raw, parsed) {
+        (Ok((raw_rest, _)), Ok((rest, rab_e))) if raw_rest.len() == rest.len() => {
+            salvage
+                .storeys
+                .push(Storey::Parsed(salvage.building.rab_e.len()));
+            salvage.building.rab_e.push(rab_e);
+            (Some(rest), None)
+        }
+        (Ok((raw_rest, raw)), _) => {
+            salvage.storeys.push(Storey::Raw(raw));
+            (Some(raw_rest), Some(format!("{}: damaged, kept raw", name)))
+        }
+        (Err(_), _) => (None, Some(format!("{}: damaged, skipped", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::rab_e_sig_test::{building_test_sig, rab_e_test_sig, read_test_sig};
+
+    fn storey_2() -> Vec<u8> {
+        rab_e_test_sig(2, 3.0, &[], &["test_sig/walls/wall.test"])
+    }
+    fn test_chg(storey_2: Vec<u8>) -> Vec<u8> {
+        building_test_sig(&[
+            rab_e_test_sig(1, 3.0, &["test_sig/columns/column_box.test"], &[]),
+            storey_2,
+            read_test_sig("test_sig/slits/2slits.test"),
+        ])
+    }
+
+    #[test]
+    fn salvage_intact() {
+        let original_in = test_chg(storey_2());
+        let salvage = salvage(&original_in);
+        assert!(salvage.damage.is_empty());
+        assert_eq!(salvage.building.rab_e.len(), 2);
+        assert!(salvage.raw_storeys().is_empty());
+        assert_eq!(salvage.write(), original_in);
+    }
+    #[test]
+    fn salvage_raw_storey() {
+        //Лишний байт в конце этажа, длина в заголовке увеличена
+        let mut storey = storey_2();
+        storey.push(0);
+        let offset = u64::from_le_bytes(*array_ref!(storey, 13, 8)) + 1;
+        storey[13..21].copy_from_slice(&offset.to_le_bytes());
+        let original_in = test_chg(storey);
+        let salvage = salvage(&original_in);
+        assert_eq!(salvage.building.rab_e.len(), 1);
+        assert_eq!(salvage.raw_storeys().len(), 1);
+        assert_eq!(salvage.damage.len(), 1);
+        assert_eq!(salvage.damage[0].what, "rab.e2: damaged, kept raw");
+        assert!(salvage.building.slits_slt.is_some());
+        assert_eq!(salvage.write(), original_in);
+    }
+    #[test]
+    fn salvage_garbage() {
+        let original_in = test_chg(storey_2());
+        let storey_1 = rab_e_test_sig(1, 3.0, &["test_sig/columns/column_box.test"], &[]);
+        let at = 10 + storey_1.len();
+        let mut damaged = original_in.clone();
+        damaged.splice(at..at, b"garbage".iter().cloned());
+        let salvage = salvage(&damaged);
+        assert_eq!(
+            salvage.damage,
+            vec![Damage {
+                offset: at,
+                len: 7,
+                what: "unknown data".to_string()
+            }]
+        );
+        assert_eq!(salvage.building.rab_e.len(), 2);
+        assert_eq!(salvage.write(), original_in);
+    }
+    #[test]
+    fn salvage_truncated() {
+        let original_in = test_chg(storey_2());
+        let cut = original_in.len() - 20;
+        let salvage = salvage(&original_in[..cut]);
+        assert_eq!(salvage.building.rab_e.len(), 2);
+        assert!(salvage.building.slits_slt.is_none());
+        assert_eq!(salvage.damage.len(), 1);
+        assert_eq!(salvage.damage[0].what, "slits.slt: damaged, skipped");
+        assert_eq!(salvage.damage[0].offset + salvage.damage[0].len, cut);
+    }
+    #[test]
+    fn salvage_panic() {
+        //Неизвестный тип сечения колонны: парсер этажа паникует, этаж остается сырым
+        let column = read_test_sig("test_sig/columns/column_box.test");
+        let mut storey = rab_e_test_sig(1, 3.0, &["test_sig/columns/column_box.test"], &[]);
+        let type_sec = storey.len() - column.len() + 90;
+        storey[type_sec] = 0xff;
+        let original_in = building_test_sig(&[
+            storey,
+            storey_2(),
+            read_test_sig("test_sig/slits/2slits.test"),
+        ]);
+        let salvage = salvage(&original_in);
+        assert_eq!(salvage.building.rab_e.len(), 1);
+        assert_eq!(salvage.raw_storeys().len(), 1);
+        assert_eq!(salvage.damage.len(), 1);
+        assert_eq!(salvage.damage[0].what, "rab.e1: damaged, kept raw");
+        assert!(salvage.building.slits_slt.is_some());
+        assert_eq!(salvage.write(), original_in);
+    }
+}
@@ -1,11 +1,13 @@
+use crate::geometry;
 use crate::sig::rab_e::{read_point, Point};
-use crate::sig::{offset, HasWrite};
+use crate::sig::{count_num, offset, HasWrite};
 use nom::{
     bytes::complete::{tag, take},
     multi::count,
     number::complete::{le_f32, le_i32, le_u16, le_u64},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 #[derive(Debug)]
@@ -41,14 +43,17 @@ impl fmt::Display for SlitsSltRaw {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SlitsSlt {
+    #[serde(with = "crate::sig::hex")]
     pub flag_line: [u8; 3],
     pub slits_num: u16,
     pub slits: Vec<Slit>,
     pub sig1_num: u16,
+    #[serde(with = "crate::sig::hex")]
     pub sig1_source: Vec<u8>, //Сигнатуры по 16b
     pub sig2_num: u16,
+    #[serde(with = "crate::sig::hex")]
     pub sig2_source: Vec<u8>, //Сигнатуры по 34b
 }
 impl HasWrite for SlitsSlt {
@@ -73,6 +78,23 @@ impl HasWrite for SlitsSlt {
         "slits.slt"
     }
 }
+impl SlitsSlt {
+    /// Пересчет счетчиков по длинам векторов, сигнатуры - целым числом по 16b и 34b
+    pub fn recount(&mut self) -> Result<(), String> {
+        if !self.sig1_source.len().is_multiple_of(16) || !self.sig2_source.len().is_multiple_of(34)
+        {
+            return Err(format!(
+                "slits.slt sig1_source.len {}, sig2_source.len {}: not by 16b and 34b",
+                self.sig1_source.len(),
+                self.sig2_source.len()
+            ));
+        }
+        self.slits_num = count_num("slits", self.slits.len())?;
+        self.sig1_num = count_num("slits sig1", self.sig1_source.len() / 16)?;
+        self.sig2_num = count_num("slits sig2", self.sig2_source.len() / 34)?;
+        Ok(())
+    }
+}
 impl fmt::Display for SlitsSlt {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}; flag_line: [", &self.name())?;
@@ -95,16 +117,24 @@ impl fmt::Display for SlitsSlt {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Slit {
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 52>"
+    )]
     name: Vec<u8>, //52b
-    p1: Point,     //Первая точка разреза
-    p2: Point,     //Вторая точка разреза
-    r_ver: i32,    //Зависит от расчета. 0=без, -1=расчет, МКЭ
+    p1: Point,  //Первая точка разреза
+    p2: Point,  //Вторая точка разреза
+    r_ver: i32, //Зависит от расчета. 0=без, -1=расчет, МКЭ
     //2b
     d1: f32, //Смещение зоны разреза вперед
     d2: f32, //Смещение зоны разреза назад
     //12b
+    #[serde(
+        serialize_with = "crate::sig::hex::serialize",
+        deserialize_with = "crate::sig::hex::deserialize_len::<_, 14>"
+    )]
     ws: Vec<u8>, //14b
 }
 impl HasWrite for Slit {
@@ -139,12 +169,6 @@ fn to_vector(p1: &Point, p2: &Point) -> Point {
         y: p2.y - p1.y,
     }
 }
-fn scalar_product(v: Point, w: Point) -> f32 {
-    v.x * w.x + v.y * w.y
-}
-fn pseudoscalar_product(v: Point, w: Point) -> f32 {
-    v.x * w.y - v.y * w.x
-}
 impl Slit {
     pub fn get_name(&self) -> Option<String> {
         let mut str = vec![];
@@ -155,34 +179,95 @@ impl Slit {
         }
         String::from_utf8(str).ok()
     }
-    pub fn inside(&self, p1: &Point, p2: &Point) -> bool {
-        let p_scalar_prod = pseudoscalar_product(to_vector(&self.p1, &self.p2), to_vector(p1, p2));
-
-        if (p_scalar_prod * 100.0).round() != 0.0 {
-            //not collinear
-            return false;
+    /// Координаты точки в системе разреза: вдоль разреза от p1 и расстояние от линии
+    /// разреза (больше 0 - слева, против часовой стрелки)
+    fn local(&self, p: &Point) -> (f32, f32) {
+        let slit = to_vector(&self.p1, &self.p2);
+        let length = geometry::length(&slit);
+        let v = to_vector(&self.p1, p);
+        (
+            geometry::dot(&slit, &v) / length,
+            geometry::cross(&slit, &v) / length,
+        )
+    }
+    /// Зона разреза: прямоугольник вдоль разреза от -d2 до d1 по нормали
+    pub fn band(&self) -> Vec<Point> {
+        let slit = to_vector(&self.p1, &self.p2);
+        let length = geometry::length(&slit);
+        if length == 0.0 {
+            return vec![];
         }
-
-        let p_scalar_prod =
-            pseudoscalar_product(to_vector(&self.p1, &self.p2), to_vector(&self.p1, p1));
-        let vector_length = (self.p1.x * self.p1.x + self.p2.y * self.p2.y).sqrt();
-        let distance = p_scalar_prod / vector_length; //negative counterclockwise
-
-        if distance > self.d1 || distance < -self.d2 {
-            //not in distance
-            return false;
+        //Единичная нормаль влево
+        let (nx, ny) = (-slit.y / length, slit.x / length);
+        let shift = |p: &Point, d: f32| geometry::point(p.x + nx * d, p.y + ny * d);
+        vec![
+            shift(&self.p1, -self.d2),
+            shift(&self.p2, -self.d2),
+            shift(&self.p2, self.d1),
+            shift(&self.p1, self.d1),
+        ]
+    }
+    /// Параметры t (0..1) части отрезка p1-p2 внутри прямоугольника в системе разреза:
+    /// u от u.0 до u.1 вдоль разреза, v от v.0 до v.1 по нормали (Лианг-Барски: p * t <= q)
+    fn clip(&self, p1: &Point, p2: &Point, u: (f32, f32), v: (f32, f32)) -> Option<(f32, f32)> {
+        let (u0, v0) = self.local(p1);
+        let (u1, v1) = self.local(p2);
+        let (du, dv) = (u1 - u0, v1 - v0);
+        let mut t = (0f32, 1f32);
+        for &(p, q) in &[
+            (-du, u0 - u.0),
+            (du, u.1 - u0),
+            (-dv, v0 - v.0),
+            (dv, v.1 - v0),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+            } else if p < 0.0 {
+                t.0 = t.0.max(q / p);
+            } else {
+                t.1 = t.1.min(q / p);
+            }
         }
-
-        if scalar_product(to_vector(&self.p1, &self.p2), to_vector(&self.p1, p1)) < 0.0
-            || scalar_product(to_vector(&self.p1, &self.p2), to_vector(&self.p1, p2)) < 0.0
-            || scalar_product(to_vector(&self.p2, &self.p1), to_vector(&self.p2, p1)) < 0.0
-            || scalar_product(to_vector(&self.p2, &self.p1), to_vector(&self.p2, p2)) < 0.0
-        {
-            //out of range
+        if t.0 > t.1 {
+            return None;
+        }
+        Some(t)
+    }
+    /// Длина части отрезка p1-p2 внутри зоны разреза. None - отрезок не заходит в зону:
+    /// лежит вне ее или только касается границы зоны или конца разреза.
+    /// Отрезок, пересекающий линию разреза поперек, дает длину внутри зоны (0 при d1 = d2 = 0)
+    pub fn overlap(&self, p1: &Point, p2: &Point) -> Option<f32> {
+        let length = geometry::distance(&self.p1, &self.p2);
+        if length == 0.0 {
+            return None;
+        }
+        let eps = geometry::EPS;
+        //Зона без границы, но сама линия разреза - с допуском
+        let interior = ((-self.d2 + eps).min(-eps), (self.d1 - eps).max(eps));
+        self.clip(p1, p2, (eps, length - eps), interior)?;
+        let band = (-self.d2 - eps, self.d1 + eps);
+        let t = self.clip(p1, p2, (-eps, length + eps), band)?;
+        Some(geometry::distance(p1, p2) * (t.1 - t.0))
+    }
+    /// Отрезок p1-p2 заходит в зону разреза
+    pub fn inside(&self, p1: &Point, p2: &Point) -> bool {
+        self.overlap(p1, p2).is_some()
+    }
+    /// Многоугольник заходит в зону разреза: сторона заходит в зону или зона лежит
+    /// внутри многоугольника. Касание границы зоны, как и у отрезков, не считается
+    pub fn polygon_inside(&self, polygon: &[Point]) -> bool {
+        let band = self.band();
+        if band.is_empty() {
             return false;
         }
-
-        true
+        let center = geometry::point(
+            band.iter().map(|p| p.x).sum::<f32>() / 4.0,
+            band.iter().map(|p| p.y).sum::<f32>() / 4.0,
+        );
+        geometry::edges(polygon).any(|(a, b)| self.inside(a, b))
+            || geometry::point_in_polygon(&center, polygon)
     }
     pub fn angle(&self) -> f32 {
         let mut vector = to_vector(&self.p1, &self.p2);
@@ -315,3 +400,119 @@ fn slits1_full_value_test() {
     };
     assert_eq!(slits.write(), c_slits.write())
 }
+
+#[cfg(test)]
+fn test_slit(p1: (f32, f32), p2: (f32, f32), d1: f32, d2: f32) -> Slit {
+    Slit {
+        name: vec![0u8; 52],
+        p1: Point { x: p1.0, y: p1.1 },
+        p2: Point { x: p2.0, y: p2.1 },
+        r_ver: 0i32,
+        d1,
+        d2,
+        ws: vec![0u8; 14],
+    }
+}
+#[cfg(test)]
+fn assert_overlap(slit: &Slit, p1: (f32, f32), p2: (f32, f32), length: Option<f32>) {
+    let overlap = slit.overlap(&Point { x: p1.0, y: p1.1 }, &Point { x: p2.0, y: p2.1 });
+    match (overlap, length) {
+        (Some(a), Some(b)) => assert!((a - b).abs() < 1e-3, "{} != {}", a, b),
+        (a, b) => assert_eq!(a, b),
+    }
+}
+#[test]
+fn slit_overlap_test() {
+    //Разрез вдоль оси y вдали от начала координат: длина разреза 6, не sqrt(x1² + y2²)
+    let slit = test_slit((10.0, 0.0), (10.0, 6.0), 0.12, 0.22);
+    //На линии разреза
+    assert_overlap(&slit, (10.0, 1.0), (10.0, 5.0), Some(4.0));
+    //Параллельно в зоне, справа и слева
+    assert_overlap(&slit, (10.2, 1.0), (10.2, 5.0), Some(4.0));
+    assert_overlap(&slit, (9.9, 1.0), (9.9, 5.0), Some(4.0));
+    //Параллельно вне зоны
+    assert_overlap(&slit, (10.3, 1.0), (10.3, 5.0), None);
+    //Частично за концом разреза
+    assert_overlap(&slit, (10.0, 4.0), (10.0, 9.0), Some(2.0));
+    assert_overlap(&slit, (10.0, 7.0), (10.0, 9.0), None);
+    //Поперек линии разреза: длина внутри зоны d1 + d2
+    assert_overlap(&slit, (9.0, 3.0), (11.0, 3.0), Some(0.34));
+    //Почти коллинеарный отрезок, частично в зоне
+    assert_overlap(&slit, (10.0, 0.0), (10.4, 4.0), Some(2.211));
+    assert!(slit.inside(&Point { x: 10.0, y: 3.0 }, &Point { x: 12.0, y: 3.0 }));
+}
+#[test]
+fn slit_touch_test() {
+    let slit = test_slit((10.0, 0.0), (10.0, 6.0), 0.12, 0.22);
+    //Стена примыкает к границе зоны торцом и лежит вдоль границы
+    assert_overlap(&slit, (10.22, 3.0), (12.0, 3.0), None);
+    assert_overlap(&slit, (9.88, 1.0), (9.88, 5.0), None);
+    assert!(!slit.inside(&Point { x: 10.22, y: 3.0 }, &Point { x: 12.0, y: 3.0 }));
+    //Касание конца разреза: поперек через конец и продолжение разреза
+    assert_overlap(&slit, (9.0, 6.0), (11.0, 6.0), None);
+    assert_overlap(&slit, (10.0, -2.0), (10.0, 0.0), None);
+    //Заходит в зону чуть дальше границы
+    assert_overlap(&slit, (10.2, 3.0), (12.0, 3.0), Some(0.02));
+}
+#[test]
+fn slit_band_test() {
+    let slit = test_slit((0.0, 0.0), (6.0, 0.0), 0.1, 0.2);
+    let band = slit.band();
+    assert_eq!(band[0], Point { x: 0.0, y: -0.2 });
+    assert_eq!(band[2], Point { x: 6.0, y: 0.1 });
+    //Плита, которую пересекает разрез, и плита в стороне
+    let cut = vec![
+        Point { x: 2.0, y: -1.0 },
+        Point { x: 4.0, y: -1.0 },
+        Point { x: 4.0, y: 1.0 },
+        Point { x: 2.0, y: 1.0 },
+    ];
+    assert!(geometry::polygons_intersect(&cut, &band));
+    let aside: Vec<Point> = cut
+        .iter()
+        .map(|p| Point {
+            x: p.x,
+            y: p.y + 3.0,
+        })
+        .collect();
+    assert!(!geometry::polygons_intersect(&aside, &band));
+}
+#[test]
+fn slit_polygon_test() {
+    let slit = test_slit((0.0, 0.0), (6.0, 0.0), 0.1, 0.2);
+    let rect = |x0: f32, y0: f32, x1: f32, y1: f32| {
+        vec![
+            Point { x: x0, y: y0 },
+            Point { x: x1, y: y0 },
+            Point { x: x1, y: y1 },
+            Point { x: x0, y: y1 },
+        ]
+    };
+    //Плита поперек зоны, плита, накрывающая зону целиком, и плита, заходящая в зону
+    assert!(slit.polygon_inside(&rect(2.0, -1.0, 4.0, 1.0)));
+    assert!(slit.polygon_inside(&rect(-1.0, -1.0, 7.0, 1.0)));
+    assert!(slit.polygon_inside(&rect(2.0, 0.05, 4.0, 1.0)));
+    //Плиты, которые только касаются границы зоны или конца разреза
+    assert!(!slit.polygon_inside(&rect(2.0, 0.1, 4.0, 1.0)));
+    assert!(!slit.polygon_inside(&rect(2.0, -1.0, 4.0, -0.2)));
+    assert!(!slit.polygon_inside(&rect(6.0, -1.0, 8.0, 1.0)));
+    //Плита в стороне
+    assert!(!slit.polygon_inside(&rect(2.0, 2.0, 4.0, 4.0)));
+}
+#[test]
+fn slit_zero_band_test() {
+    let slit = test_slit((0.0, 0.0), (6.0, 6.0), 0.0, 0.0);
+    assert_overlap(&slit, (1.0, 1.0), (2.0, 2.0), Some(2f32.sqrt()));
+    assert_overlap(&slit, (0.0, 2.0), (2.0, 0.0), Some(0.0));
+    assert_overlap(&slit, (0.0, 1.0), (1.0, 2.0), None);
+    assert_eq!(slit.band().len(), 4);
+    assert!(test_slit((1.0, 1.0), (1.0, 1.0), 0.1, 0.1)
+        .band()
+        .is_empty());
+    assert_overlap(
+        &test_slit((1.0, 1.0), (1.0, 1.0), 0.1, 0.1),
+        (0.0, 1.0),
+        (2.0, 1.0),
+        None,
+    );
+}
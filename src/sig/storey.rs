@@ -0,0 +1,106 @@
+//! Этажи здания с абсолютными отметками
+//!
+//! Единый источник отметок Z: этажи упорядочены по номеру, отметка низа этажа
+//! равна сумме высот нижележащих этажей.
+use crate::sig::building::Building;
+use crate::sig::rab_e::rab_e::RabE;
+use std::fmt;
+
+/// Этаж здания
+#[derive(Debug)]
+pub struct Storey<'a> {
+    pub index: usize,    //Порядковый номер этажа снизу, с 0
+    pub etazh_num: u16,  //Номер этажа из заголовка rab.e
+    pub level: f32,      //Отметка низа этажа, м
+    pub height: f32,     //Высота этажа, м
+    pub rab_e: &'a RabE, //Сигнатура этажа
+}
+impl<'a> Storey<'a> {
+    /// Отметка верха этажа, м
+    pub fn top(&self) -> f32 {
+        self.level + self.height
+    }
+    /// Имя этажа для отчетов - номер и отметка низа: "Этаж 3 (+9.600)"
+    pub fn title(&self) -> String {
+        format!("Этаж {} ({:+.3})", self.etazh_num, self.level)
+    }
+}
+impl<'a> fmt::Display for Storey<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}..{:+.3}, h = {}",
+            &self.title(),
+            &self.top(),
+            &self.height
+        )
+    }
+}
+
+impl Building {
+    /// Этажи здания снизу вверх с отметками
+    ///
+    /// Сигнатуры rab.e лежат в файле в алфавитном порядке (rab.e1, rab.e10, rab.e2...),
+    /// поэтому этажи сортируются по etazh_num.
+    pub fn storeys(&self) -> Vec<Storey<'_>> {
+        let mut rab_e: Vec<&RabE> = self.rab_e.iter().collect();
+        rab_e.sort_by_key(|r| r.head.etazh_num);
+        let mut level = 0f32;
+        let mut storeys = vec![];
+        for (index, r) in rab_e.into_iter().enumerate() {
+            let height = r.head.etazh_h;
+            storeys.push(Storey {
+                index,
+                etazh_num: r.head.etazh_num,
+                level,
+                height,
+                rab_e: r,
+            });
+            level += height;
+        }
+        storeys
+    }
+}
+
+#[cfg(test)]
+fn test_building() -> Building {
+    use crate::tests::rab_e_sig_test::{rab_e_test_sig, read_test_building};
+    read_test_building(&[
+        rab_e_test_sig(1, 3.3, &["test_sig/columns/column_box.test"], &[]),
+        rab_e_test_sig(10, 2.8, &[], &["test_sig/walls/wall.test"]),
+        rab_e_test_sig(2, 3.0, &[], &[]),
+    ])
+}
+#[test]
+fn storeys_order_test() {
+    let building = test_building();
+    let storeys = building.storeys();
+    let nums: Vec<u16> = storeys.iter().map(|s| s.etazh_num).collect();
+    assert_eq!(nums, vec![1, 2, 10]);
+    let index: Vec<usize> = storeys.iter().map(|s| s.index).collect();
+    assert_eq!(index, vec![0, 1, 2]);
+}
+#[test]
+fn storeys_level_test() {
+    let building = test_building();
+    let storeys = building.storeys();
+    assert_eq!(storeys[0].level, 0.0);
+    assert!((storeys[1].level - 3.3).abs() < 1e-6);
+    assert!((storeys[2].level - 6.3).abs() < 1e-6);
+    assert!((storeys[2].top() - 9.1).abs() < 1e-5);
+    assert_eq!(storeys[2].rab_e.wall.len(), 1);
+    assert_eq!(storeys[0].rab_e.column.len(), 1);
+}
+#[test]
+fn storeys_title_test() {
+    let building = test_building();
+    let titles: Vec<String> = building.storeys().iter().map(|s| s.title()).collect();
+    assert_eq!(
+        titles,
+        vec!["Этаж 1 (+0.000)", "Этаж 2 (+3.300)", "Этаж 10 (+6.300)"]
+    );
+    assert_eq!(
+        building.storeys()[1].to_string(),
+        "Этаж 2 (+3.300)..+6.300, h = 3"
+    );
+}
@@ -1,13 +1,52 @@
+//! Элементы в разрезах для ЛИРА
+//!
+//! По *.chg (разрезы и элементы) и *.ald (блоки и конечные элементы ЛИРА) пишет в
+//! каталог slits_fe.txt - номера КЭ элементов каждого разреза, slits_angle.txt - углы
+//! разрезов, slits_name.txt - имена разрезов. Фильтр элементов - XML или TOML файл.
+use crate::sig::element::{Footprint, Kind};
 use crate::sig::*;
 use serde::Deserialize;
-use std::io::{Read, Write};
+use std::fmt;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
+
+/// Ошибка выгрузки
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, std::io::Error),
+    Xml(PathBuf, quick_xml::de::DeError),
+    Toml(PathBuf, toml::de::Error),
+    Parse(PathBuf, String), //Ошибка разбора *.chg
+    NoSlits,                //В *.chg нет разрезов
+    Block(PathBuf, usize),  //КЭ ссылается на несуществующий блок
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, why) => write!(f, "couldn't read or write {}: {}", path.display(), why),
+            Error::Xml(path, why) => write!(f, "couldn't parse {}: {}", path.display(), why),
+            Error::Toml(path, why) => write!(f, "couldn't parse {}: {}", path.display(), why),
+            Error::Parse(path, why) => write!(f, "couldn't parse {}: {}", path.display(), why),
+            Error::NoSlits => write!(f, "no slits in building"),
+            Error::Block(path, num) => write!(f, "{}: no block {}", path.display(), num),
+        }
+    }
+}
+impl std::error::Error for Error {}
+
+fn read_text(path: &Path) -> Result<String, Error> {
+    match std::fs::read(path) {
+        Err(why) => Err(Error::Io(path.to_path_buf(), why)),
+        Ok(source) => Ok(String::from_utf8_lossy(&source).to_string()),
+    }
+}
 
 struct ShortNameBlock {
     element_type: TypeBlock,
     element_num: usize,
     storey: usize,
+    mat: Option<u16>,       //Номер материала
+    thickness: Option<f32>, //Толщина стены или плиты, см
+    sec: Option<String>,    //Тип сечения балки
 }
 #[derive(PartialEq)]
 enum TypeBlock {
@@ -35,6 +74,20 @@ impl ShortNameBlock {
             element_type,
             element_num,
             storey,
+            mat: None,
+            thickness: None,
+            sec: None,
+        }
+    }
+}
+impl TypeBlock {
+    /// Имя типа как в Kind::name
+    fn kind_name(&self) -> &str {
+        match self {
+            TypeBlock::Beam => "beam",
+            TypeBlock::Slab => "slab",
+            TypeBlock::Fslab => "f_slab",
+            TypeBlock::Wall => "wall",
         }
     }
 }
@@ -88,153 +141,140 @@ fn range_to_string(range_start: u64, range_end: u64) -> String {
         range_start.to_string() + "-" + &range_end.to_string() + " "
     }
 }
+/// Номера КЭ строкой диапазонов: "5-7 9 "
 fn write_elements(elements: &[u64]) -> String {
     let mut str = String::new();
-    if elements.is_empty() {
-        return str;
-    }
-    let mut range_start = elements[0];
-    let mut range_end = elements[0];
-    for i in 1..elements.iter().len() {
-        if elements[i - 1] == elements[i] - 1 {
-            range_end = elements[i];
+    let (mut range_start, mut range_end) = match elements.first() {
+        None => return str,
+        Some(&fe) => (fe, fe),
+    };
+    for &fe in elements[1..].iter() {
+        if range_end + 1 == fe {
+            range_end = fe;
         } else {
             str = str + &range_to_string(range_start, range_end);
-            range_start = elements[i];
-            range_end = elements[i];
-        }
-        if i == elements.len() - 1 {
-            str = str + &range_to_string(range_start, range_end);
+            range_start = fe;
+            range_end = fe;
         }
     }
-    str
+    str + &range_to_string(range_start, range_end)
 }
 
+/// Фильтр элементов разреза
+///
+/// Условия одного фильтра объединяются по И, пустое условие не ограничивает. Вложенные
+/// фильтры all объединяются по И, any - по ИЛИ. XML:
+/// `<Filter><any><kind>wall</kind><b_min>20</b_min></any><any><kind>slab</kind></any></Filter>`,
+/// TOML: `any = [{ kind = ["wall"], b_min = 20.0 }, { kind = ["slab"] }]`
 #[derive(Debug, Deserialize, PartialEq)]
-struct InputPath {
-    #[serde(rename = "Path")]
-    paths: Vec<String>,
+#[serde(default)]
+pub struct Filter {
+    beams: bool,         //Балки
+    walls: bool,         //Стены
+    etazh_from: usize,   //Номер этажа от
+    etazh_to: usize,     //Номер этажа до
+    kind: Vec<String>,   //Типы элементов: wall, beam, slab, f_slab
+    mat: Vec<u16>,       //Номера материалов
+    sec: Vec<String>,    //Типы сечений балок: rectangle, circle, cross, ring, box, i_sec, shelves
+    b_min: Option<f32>,  //Толщина стены или плиты от, см
+    b_max: Option<f32>,  //Толщина стены или плиты до, см
+    storeys: Vec<usize>, //Номера этажей
+    all: Vec<Filter>,
+    any: Vec<Filter>,
 }
-impl InputPath {
-    fn read_paths() -> InputPath {
-        let path = Path::new(r"path.xml");
-        let display = path.display();
-        let mut file = match std::fs::File::open(&path) {
-            Err(why) => panic!("couldn't open {}: {}", display, why),
-            Ok(file) => file,
-        };
-        let mut source_file: Vec<u8> = vec![];
-        if let Err(why) = file.read_to_end(&mut source_file) {
-            panic!("couldn't read {}: {}", display, why)
-        };
-        let text = String::from_utf8_lossy(&source_file).to_string();
-        let res: InputPath = quick_xml::de::from_str(&text).unwrap();
-        res
-    }
-    fn get_ald_path(&self) -> PathBuf {
-        for str in &self.paths {
-            let dir_ent = WalkDir::new(str)
-                .into_iter()
-                .filter_map(Result::ok)
-                .find(|e| e.file_name().to_string_lossy() == (read_name() + ".ald"));
-            if let Some(dir) = dir_ent {
-                return dir.into_path();
-            }
+impl Default for Filter {
+    /// Без ограничений
+    fn default() -> Filter {
+        Filter {
+            beams: true,
+            walls: true,
+            etazh_from: 0,
+            etazh_to: usize::MAX,
+            kind: vec![],
+            mat: vec![],
+            sec: vec![],
+            b_min: None,
+            b_max: None,
+            storeys: vec![],
+            all: vec![],
+            any: vec![],
         }
-        panic!("couldn't find .ald file");
-    }
-    fn get_chg_path(&self) -> PathBuf {
-        for str in &self.paths {
-            let dir_ent = WalkDir::new(str)
-                .into_iter()
-                .filter_map(Result::ok)
-                .find(|e| e.file_name().to_string_lossy() == (read_name() + ".chg"));
-            if let Some(dir) = dir_ent {
-                return dir.into_path();
-            }
-        }
-        panic!("couldn't find .chg file");
     }
 }
+/// Допуск сравнения толщины, см
+const B_EPS: f32 = 1e-3;
 
-#[derive(Debug, Deserialize, PartialEq)]
-struct Filter {
-    beams: bool,
-    walls: bool,
-    etazh_from: usize,
-    etazh_to: usize,
-}
 impl Filter {
-    fn read_filter() -> Filter {
-        let path = Path::new(r"filter.xml");
-        let display = path.display();
-        let mut file = match std::fs::File::open(&path) {
-            Err(why) => panic!("couldn't open {}: {}", display, why),
-            Ok(file) => file,
-        };
-        let mut source_file: Vec<u8> = vec![];
-        if let Err(why) = file.read_to_end(&mut source_file) {
-            panic!("couldn't read {}: {}", display, why)
-        };
-        let text = String::from_utf8_lossy(&source_file).to_string();
-        let res: Filter = quick_xml::de::from_str(&text).unwrap();
-        res
+    /// Фильтр из *.toml или XML (другие расширения)
+    pub fn read(path: &Path) -> Result<Filter, Error> {
+        let text = read_text(path)?;
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => {
+                toml::from_str(&text).map_err(|why| Error::Toml(path.to_path_buf(), why))
+            }
+            _ => quick_xml::de::from_str(&text).map_err(|why| Error::Xml(path.to_path_buf(), why)),
+        }
     }
 
     fn check(&self, block: &ShortNameBlock) -> bool {
-        if !self.beams && block.element_type != TypeBlock::Beam {
+        if !self.beams && block.element_type == TypeBlock::Beam {
             return false;
         }
-        if !self.walls && block.element_type != TypeBlock::Wall {
+        if !self.walls && block.element_type == TypeBlock::Wall {
             return false;
         }
-        if self.etazh_from > block.storey {
+        if self.etazh_from > block.storey || self.etazh_to < block.storey {
             return false;
         }
-        if self.etazh_to < block.storey {
+        if !self.storeys.is_empty() && !self.storeys.contains(&block.storey) {
             return false;
         }
-        true
+        let kind = block.element_type.kind_name();
+        if !self.kind.is_empty() && !self.kind.iter().any(|k| k == kind) {
+            return false;
+        }
+        if !self.mat.is_empty() && !block.mat.is_some_and(|mat| self.mat.contains(&mat)) {
+            return false;
+        }
+        if !self.sec.is_empty() && !block.sec.as_ref().is_some_and(|sec| self.sec.contains(sec)) {
+            return false;
+        }
+        let b_min = self
+            .b_min
+            .is_none_or(|min| block.thickness.is_some_and(|b| b >= min - B_EPS));
+        let b_max = self
+            .b_max
+            .is_none_or(|max| block.thickness.is_some_and(|b| b <= max + B_EPS));
+        if !b_min || !b_max {
+            return false;
+        }
+        self.all.iter().all(|filter| filter.check(block))
+            && (self.any.is_empty() || self.any.iter().any(|filter| filter.check(block)))
     }
 }
-fn read_ald(path: &Path) -> Vec<ElBlock> {
-    let display = path.display();
-    let mut file = match std::fs::File::open(&path) {
-        Err(why) => panic!("couldn't open {}: {}", display, why),
-        Ok(file) => file,
-    };
-    let mut source_file: Vec<u8> = vec![];
-    if let Err(why) = file.read_to_end(&mut source_file) {
-        panic!("couldn't read {}: {}", display, why)
-    };
-    let text = String::from_utf8_lossy(&source_file).to_string();
-    let mut res: LiraProject = quick_xml::de::from_str(&text).unwrap();
+fn read_ald(path: &Path) -> Result<Vec<ElBlock>, Error> {
+    let mut res: LiraProject = quick_xml::de::from_str(&read_text(path)?)
+        .map_err(|why| Error::Xml(path.to_path_buf(), why))?;
+    let blocks = &mut res.block_array.el_blocks;
     for element in res.elem_array.elements.iter() {
-        let block_i = element.block_num - 1;
-        res.block_array.el_blocks[block_i].fe.push(element.fe_num);
+        let block = element
+            .block_num
+            .checked_sub(1)
+            .and_then(|i| blocks.get_mut(i));
+        match block {
+            None => return Err(Error::Block(path.to_path_buf(), element.block_num)),
+            Some(block) => block.fe.push(element.fe_num),
+        }
     }
-    res.block_array.el_blocks
+    Ok(res.block_array.el_blocks)
 }
 
-fn read_name() -> String {
-    let path = Path::new(r"Data\name.txt");
-    let display = path.display();
-    let mut file = match std::fs::File::open(&path) {
-        Err(why) => panic!("couldn't open {}: {}", display, why),
-        Ok(file) => file,
-    };
-    let mut source_file: Vec<u8> = vec![];
-    if let Err(why) = file.read_to_end(&mut source_file) {
-        panic!("couldn't read {}: {}", display, why)
-    };
-    let mut text = String::from_utf8_lossy(&source_file).trim().to_string();
-    text.remove(0);
-    text
-}
-
-fn get_selection(path_ald: &Path, build: &building::Building, filter: Filter) -> Vec<String> {
-    let el_slits = element_in_slits(build);
-    let el_block = read_ald(path_ald);
+fn get_selection(
+    el_block: &[ElBlock],
+    build: &building::Building,
+    filter: &Filter,
+) -> Result<Vec<String>, Error> {
+    let el_slits = element_in_slits(build)?;
     let mut out = vec![];
     for blocks in el_slits {
         let mut fe = vec![];
@@ -250,83 +290,291 @@ fn get_selection(path_ald: &Path, build: &building::Building, filter: Filter) ->
         //fe.sort_unstable();
         out.push(write_elements(&fe));
     }
-    out
+    Ok(out)
+}
+
+fn slits(build: &building::Building) -> Result<&[Slit], Error> {
+    match &build.slits_slt {
+        None => Err(Error::NoSlits),
+        Some(slits_slt) => Ok(&slits_slt.slits),
+    }
 }
 
-fn element_in_slits(build: &building::Building) -> Vec<Vec<ShortNameBlock>> {
+fn element_in_slits(build: &building::Building) -> Result<Vec<Vec<ShortNameBlock>>, Error> {
     let mut slits_elem_vec: Vec<Vec<ShortNameBlock>> = vec![];
-    let slits = &build.slits_slt.as_ref().unwrap().slits;
-    let rab_e = &build.rab_e;
+    let slits = slits(build)?;
+    let storeys = build.storeys();
     for slit in slits.iter() {
         let mut element_vec = vec![];
-        for etazh in rab_e.iter() {
-            let walls = &etazh.wall;
-            for (count_wall, wall) in walls.iter().enumerate() {
-                if slit.inside(wall.get_start_point(), wall.get_end_point()) {
-                    element_vec.push(ShortNameBlock::new(
-                        TypeBlock::Wall,
-                        count_wall + 1,
-                        etazh.head.etazh_num as usize,
-                    ));
-                }
-            }
-            let beams = &etazh.beam;
-            for (count_beam, beam) in beams.iter().enumerate() {
-                if slit.inside(beam.get_start_point(), beam.get_end_point()) {
-                    element_vec.push(ShortNameBlock::new(
-                        TypeBlock::Beam,
-                        count_beam + 1,
-                        etazh.head.etazh_num as usize,
-                    ));
-                }
+        for element in build.elements() {
+            let element_type = match element.kind() {
+                Kind::Wall => TypeBlock::Wall,
+                Kind::Beam => TypeBlock::Beam,
+                Kind::Slab => TypeBlock::Slab,
+                Kind::FSlab => TypeBlock::Fslab,
+                _ => continue,
+            };
+            let cut = match element.footprint() {
+                Footprint::Line(p1, p2) => slit.inside(&p1, &p2),
+                Footprint::Polygon(polygon) => slit.polygon_inside(&polygon),
+                _ => false,
+            };
+            if cut {
+                let mut block = ShortNameBlock::new(
+                    element_type,
+                    element.index() + 1,
+                    storeys[element.storey()].etazh_num as usize,
+                );
+                block.mat = element.mat();
+                block.thickness = element.thickness();
+                block.sec = element.section().map(String::from);
+                element_vec.push(block);
             }
         }
         slits_elem_vec.push(element_vec);
     }
-    slits_elem_vec
+    Ok(slits_elem_vec)
 }
-fn write_slits(slits: Vec<String>) {
-    let path_buf = Path::new(r"Data\slits_fe.txt");
-    let display = path_buf.display();
-    let mut file = match std::fs::File::create(path_buf) {
-        Err(why) => panic!("couldn't create {}: {}", display, why),
-        Ok(file) => file,
-    };
-    for slit in slits {
-        file.write((slit + "\n").as_bytes()).unwrap_or_default();
+fn write_lines(path: &Path, lines: &[String]) -> Result<(), Error> {
+    let mut text = String::new();
+    for line in lines {
+        text += line;
+        text += "\n";
     }
+    std::fs::write(path, text).map_err(|why| Error::Io(path.to_path_buf(), why))
 }
-fn write_slits_angle(build: &building::Building) {
-    let path_buf = Path::new(r"Data\slits_angle.txt");
-    let display = path_buf.display();
-    let mut file = match std::fs::File::create(path_buf) {
-        Err(why) => panic!("couldn't create {}: {}", display, why),
-        Ok(file) => file,
+/// Выгрузка разрезов здания path_chg с КЭ из path_ald в каталог out_dir
+pub fn write_slits_for_lira(
+    path_chg: &Path,
+    path_ald: &Path,
+    out_dir: &Path,
+    filter: &Filter,
+) -> Result<(), Error> {
+    let original_in =
+        std::fs::read(path_chg).map_err(|why| Error::Io(path_chg.to_path_buf(), why))?;
+    let building = match building::read_original(&original_in) {
+        Err(why) => return Err(Error::Parse(path_chg.to_path_buf(), why.to_string())),
+        Ok((_, building)) => building,
     };
-    let slits = &build.slits_slt.as_ref().unwrap().slits;
-    for slit in slits {
-        file.write((slit.angle().to_string() + "\n").as_bytes())
-            .unwrap_or_default();
-    }
+    let el_block = read_ald(path_ald)?;
+    let slits = slits(&building)?;
+    let fe = get_selection(&el_block, &building, filter)?;
+    let angle: Vec<String> = slits.iter().map(|s| s.angle().to_string()).collect();
+    let name: Vec<String> = slits
+        .iter()
+        .map(|s| s.get_name().unwrap_or_default())
+        .collect();
+    std::fs::create_dir_all(out_dir).map_err(|why| Error::Io(out_dir.to_path_buf(), why))?;
+    write_lines(&out_dir.join("slits_fe.txt"), &fe)?;
+    write_lines(&out_dir.join("slits_angle.txt"), &angle)?;
+    write_lines(&out_dir.join("slits_name.txt"), &name)
 }
-fn write_slits_name(build: &building::Building) {
-    let path_buf = Path::new(r"Data\slits_name.txt");
-    let display = path_buf.display();
-    let mut file = match std::fs::File::create(path_buf) {
-        Err(why) => panic!("couldn't create {}: {}", display, why),
-        Ok(file) => file,
-    };
-    let slits = &build.slits_slt.as_ref().unwrap().slits;
-    for slit in slits {
-        file.write((slit.get_name().unwrap_or_default() + "\n").as_bytes())
-            .unwrap_or_default();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::rab_e_sig_test::{building_test_sig, rab_e_test_sig, read_test_sig};
+
+    const ALD: &str = r#"<LIRA_Project>
+<BlockArray>
+<ElBlock Num="1" Type="1" Storey="1" ShortName="С1_1"/>
+<ElBlock Num="2" Type="2" Storey="1" ShortName="Б1_1"/>
+</BlockArray>
+<ElemBlockArray>
+<ElemBlock NumFE="5" NumBlk="1"/>
+<ElemBlock NumFE="6" NumBlk="1"/>
+<ElemBlock NumFE="7" NumBlk="1"/>
+<ElemBlock NumFE="9" NumBlk="2"/>
+</ElemBlockArray>
+</LIRA_Project>"#;
+
+    /// Каталог с *.chg (стена пересекает второй разрез) и *.ald
+    fn test_dir(name: &str, ald: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("couldn't create dir");
+        let chg = building_test_sig(&[
+            rab_e_test_sig(1, 3.0, &[], &["test_sig/walls/wall.test"]),
+            read_test_sig("test_sig/slits/2slits.test"),
+        ]);
+        std::fs::write(dir.join("a.chg"), chg).expect("couldn't write");
+        std::fs::write(dir.join("a.ald"), ald).expect("couldn't write");
+        dir
+    }
+    fn read_out(dir: &Path, name: &str) -> String {
+        std::fs::read_to_string(dir.join("out").join(name)).expect("couldn't read")
+    }
+
+    #[test]
+    fn elements_ranges() {
+        assert_eq!(write_elements(&[]), "");
+        assert_eq!(write_elements(&[7]), "7 ");
+        assert_eq!(write_elements(&[0, 1]), "0-1 ");
+        assert_eq!(write_elements(&[5, 6, 7, 9]), "5-7 9 ");
+        assert_eq!(write_elements(&[9, 5, 6]), "9 5-6 ");
+    }
+    #[test]
+    fn filter_check() {
+        let wall = ShortNameBlock::new(TypeBlock::Wall, 1, 2);
+        let beam = ShortNameBlock::new(TypeBlock::Beam, 1, 2);
+        let slab = ShortNameBlock::new(TypeBlock::Slab, 1, 2);
+        let filter = Filter {
+            beams: false,
+            walls: true,
+            etazh_from: 1,
+            etazh_to: 3,
+            ..Filter::default()
+        };
+        assert!(filter.check(&wall));
+        assert!(!filter.check(&beam));
+        assert!(filter.check(&slab));
+        let filter = Filter {
+            beams: true,
+            walls: false,
+            etazh_from: 3,
+            etazh_to: 5,
+            ..Filter::default()
+        };
+        assert!(!filter.check(&beam));
+        assert!(Filter::default().check(&wall) && Filter::default().check(&beam));
+    }
+    fn block(element_type: TypeBlock, storey: usize, mat: u16, b: Option<f32>) -> ShortNameBlock {
+        let mut block = ShortNameBlock::new(element_type, 1, storey);
+        block.mat = Some(mat);
+        block.thickness = b;
+        block
+    }
+    #[test]
+    fn filter_rules() {
+        let thin = block(TypeBlock::Wall, 1, 2, Some(20.0));
+        let thick = block(TypeBlock::Wall, 2, 2, Some(40.000_004));
+        let slab = block(TypeBlock::Slab, 2, 3, Some(20.0));
+        let mut beam = block(TypeBlock::Beam, 3, 2, None);
+        beam.sec = Some("rectangle".to_string());
+        let filter = Filter {
+            b_min: Some(30.0),
+            b_max: Some(40.0),
+            ..Filter::default()
+        };
+        assert!(!filter.check(&thin) && filter.check(&thick));
+        assert!(!filter.check(&beam));
+        let filter = Filter {
+            mat: vec![3],
+            storeys: vec![2, 3],
+            ..Filter::default()
+        };
+        assert!(filter.check(&slab) && !filter.check(&thick) && !filter.check(&thin));
+        let filter = Filter {
+            sec: vec!["rectangle".to_string()],
+            ..Filter::default()
+        };
+        assert!(filter.check(&beam) && !filter.check(&thin));
+        //Толстые стены или плиты, на этажах 2-3
+        let filter = Filter {
+            etazh_from: 2,
+            etazh_to: 3,
+            any: vec![
+                Filter {
+                    kind: vec!["wall".to_string()],
+                    b_min: Some(30.0),
+                    ..Filter::default()
+                },
+                Filter {
+                    kind: vec!["slab".to_string(), "f_slab".to_string()],
+                    ..Filter::default()
+                },
+            ],
+            ..Filter::default()
+        };
+        assert!(!filter.check(&thin) && filter.check(&thick));
+        assert!(filter.check(&slab) && !filter.check(&beam));
+        let filter = Filter {
+            all: vec![
+                Filter {
+                    mat: vec![2],
+                    ..Filter::default()
+                },
+                Filter {
+                    storeys: vec![1],
+                    ..Filter::default()
+                },
+            ],
+            ..Filter::default()
+        };
+        assert!(filter.check(&thin) && !filter.check(&thick));
+    }
+    #[test]
+    fn filter_read() {
+        let dir = std::env::temp_dir().join("parse_chg_lira_filter_test");
+        std::fs::create_dir_all(&dir).expect("couldn't create dir");
+        let expected = Filter {
+            etazh_from: 2,
+            any: vec![
+                Filter {
+                    kind: vec!["wall".to_string()],
+                    b_min: Some(20.0),
+                    ..Filter::default()
+                },
+                Filter {
+                    kind: vec!["slab".to_string(), "f_slab".to_string()],
+                    mat: vec![1],
+                    ..Filter::default()
+                },
+            ],
+            ..Filter::default()
+        };
+        let toml = "etazh_from = 2\n\
+                    any = [{ kind = [\"wall\"], b_min = 20.0 },\
+                    { kind = [\"slab\", \"f_slab\"], mat = [1] }]\n";
+        std::fs::write(dir.join("filter.toml"), toml).expect("couldn't write");
+        let filter = Filter::read(&dir.join("filter.toml")).expect("couldn't read toml");
+        assert_eq!(filter, expected);
+        let xml = "<Filter><etazh_from>2</etazh_from>\
+                   <any><kind>wall</kind><b_min>20</b_min></any>\
+                   <any><kind>slab</kind><kind>f_slab</kind><mat>1</mat></any></Filter>";
+        std::fs::write(dir.join("filter.xml"), xml).expect("couldn't write");
+        let filter = Filter::read(&dir.join("filter.xml")).expect("couldn't read xml");
+        assert_eq!(filter, expected);
+        std::fs::write(dir.join("bad.toml"), "kind = 1").expect("couldn't write");
+        let result = Filter::read(&dir.join("bad.toml"));
+        assert!(matches!(result, Err(Error::Toml(..))));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn lira_write() {
+        let dir = test_dir("parse_chg_lira_test", ALD);
+        let out = dir.join("out");
+        let (chg, ald) = (dir.join("a.chg"), dir.join("a.ald"));
+        write_slits_for_lira(&chg, &ald, &out, &Filter::default()).expect("couldn't write");
+        assert_eq!(read_out(&dir, "slits_fe.txt"), "\n5-7 \n");
+        assert_eq!(read_out(&dir, "slits_name.txt").lines().count(), 2);
+        assert_eq!(read_out(&dir, "slits_angle.txt").lines().count(), 2);
+        let path = dir.join("filter.xml");
+        let xml = "<Filter><beams>true</beams><walls>false</walls>\
+                   <etazh_from>0</etazh_from><etazh_to>10</etazh_to></Filter>";
+        std::fs::write(&path, xml).expect("couldn't write");
+        let filter = Filter::read(&path).expect("couldn't read filter");
+        write_slits_for_lira(&chg, &ald, &out, &filter).expect("couldn't write");
+        assert_eq!(read_out(&dir, "slits_fe.txt"), "\n\n");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn lira_errors() {
+        let ald = ALD.replace(r#"NumBlk="2""#, r#"NumBlk="3""#);
+        let dir = test_dir("parse_chg_lira_errors_test", &ald);
+        let (chg, out) = (dir.join("a.chg"), dir.join("out"));
+        let result = write_slits_for_lira(&chg, &dir.join("a.ald"), &out, &Filter::default());
+        assert!(matches!(result, Err(Error::Block(_, 3))));
+        let result = write_slits_for_lira(&chg, &dir.join("b.ald"), &out, &Filter::default());
+        assert!(matches!(result, Err(Error::Io(..))));
+        let result = Filter::read(&dir.join("a.chg"));
+        assert!(matches!(result, Err(Error::Xml(..))));
+        let no_slits = building_test_sig(&[rab_e_test_sig(1, 3.0, &[], &[])]);
+        std::fs::write(&chg, no_slits).expect("couldn't write");
+        std::fs::write(dir.join("a.ald"), ALD).expect("couldn't write");
+        let result = write_slits_for_lira(&chg, &dir.join("a.ald"), &out, &Filter::default());
+        assert!(matches!(result, Err(Error::NoSlits)));
+        assert!(!out.exists());
+        let _ = std::fs::remove_dir_all(&dir);
     }
-}
-pub fn write_slits_for_lira() {
-    let input = InputPath::read_paths();
-    let filter = Filter::read_filter();
-    let building = crate::read_file(&input.get_chg_path());
-    write_slits(get_selection(&input.get_ald_path(), &building, filter));
-    write_slits_angle(&building);
-    write_slits_name(&building);
 }
@@ -0,0 +1,173 @@
+//! Пространственный индекс элементов
+//!
+//! R-дерево по габаритам элементов на каждом этаже. Запросы: элементы в радиусе от
+//! точки, пересекающие многоугольник, пересекающие отрезок, ближайшая стена.
+//! Габарит отбирает кандидатов, точная проверка - по геометрии в плане (Footprint).
+use crate::geometry;
+use crate::sig::building::Building;
+use crate::sig::element::{Element, Footprint, Kind, Point};
+use rstar::{PointDistance, RTree, RTreeObject, AABB};
+
+/// Элемент в индексе
+struct Entry<'a> {
+    element: Element<'a>,
+    footprint: Footprint,
+    envelope: AABB<[f32; 2]>,
+}
+impl RTreeObject for Entry<'_> {
+    type Envelope = AABB<[f32; 2]>;
+    fn envelope(&self) -> Self::Envelope {
+        self.envelope
+    }
+}
+impl PointDistance for Entry<'_> {
+    fn distance_2(&self, point: &[f32; 2]) -> f32 {
+        self.footprint
+            .distance(&geometry::point(point[0], point[1]))
+            .powi(2)
+    }
+}
+
+fn envelope(points: &[Point]) -> AABB<[f32; 2]> {
+    AABB::from_points(points.iter().map(|p| [p.x, p.y]).collect::<Vec<_>>().iter())
+}
+
+/// Индекс здания, по дереву на этаж
+pub struct SpatialIndex<'a> {
+    storeys: Vec<RTree<Entry<'a>>>,
+}
+impl<'a> SpatialIndex<'a> {
+    /// Индекс всех элементов с геометрией в плане
+    pub fn new(building: &'a Building) -> SpatialIndex<'a> {
+        let mut entries: Vec<Vec<Entry>> = building.storeys().iter().map(|_| vec![]).collect();
+        for element in building.elements() {
+            let footprint = element.footprint();
+            let points = footprint.points();
+            if points.is_empty() {
+                continue;
+            }
+            entries[element.storey()].push(Entry {
+                element,
+                footprint,
+                envelope: envelope(&points),
+            });
+        }
+        SpatialIndex {
+            storeys: entries.into_iter().map(RTree::bulk_load).collect(),
+        }
+    }
+    fn tree(&self, storey: usize) -> Option<&RTree<Entry<'a>>> {
+        self.storeys.get(storey)
+    }
+    /// Элементы этажа не дальше radius от точки
+    pub fn within(&self, storey: usize, p: &Point, radius: f32) -> Vec<Element<'a>> {
+        match self.tree(storey) {
+            None => vec![],
+            Some(tree) => tree
+                .locate_within_distance([p.x, p.y], radius * radius)
+                .map(|entry| entry.element)
+                .collect(),
+        }
+    }
+    /// Элементы этажа, имеющие общую точку с многоугольником
+    pub fn intersecting(&self, storey: usize, polygon: &[Point]) -> Vec<Element<'a>> {
+        match self.tree(storey) {
+            None => vec![],
+            Some(_) if polygon.is_empty() => vec![],
+            Some(tree) => tree
+                .locate_in_envelope_intersecting(&envelope(polygon))
+                .filter(|entry| entry.footprint.intersects_polygon(polygon))
+                .map(|entry| entry.element)
+                .collect(),
+        }
+    }
+    /// Элементы этажа, пересекающие отрезок p1-p2
+    pub fn crossing(&self, storey: usize, p1: &Point, p2: &Point) -> Vec<Element<'a>> {
+        match self.tree(storey) {
+            None => vec![],
+            Some(tree) => tree
+                .locate_in_envelope_intersecting(&envelope(&[*p1, *p2]))
+                .filter(|entry| entry.footprint.crosses_segment(p1, p2))
+                .map(|entry| entry.element)
+                .collect(),
+        }
+    }
+    /// Ближайшая к точке стена этажа
+    pub fn nearest_wall(&self, storey: usize, p: &Point) -> Option<Element<'a>> {
+        self.tree(storey)?
+            .nearest_neighbor_iter(&[p.x, p.y])
+            .find(|entry| entry.element.kind() == Kind::Wall)
+            .map(|entry| entry.element)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::point;
+    use crate::tests::rab_e_sig_test::{rab_e_test_sig, rab_e_test_sig_full, read_test_building};
+
+    fn wall_points(building: &Building) -> (Point, Point) {
+        let element = building
+            .elements()
+            .find(|e| e.kind() == Kind::Wall)
+            .expect("no wall");
+        match element.footprint() {
+            Footprint::Line(p1, p2) => (p1, p2),
+            other => panic!("wall footprint {:?}", other),
+        }
+    }
+
+    #[test]
+    fn spatial_wall() {
+        let building = read_test_building(&[
+            rab_e_test_sig(1, 3.0, &[], &[]),
+            rab_e_test_sig(2, 3.0, &[], &["test_sig/walls/wall.test"]),
+        ]);
+        let index = SpatialIndex::new(&building);
+        let (p1, p2) = wall_points(&building);
+        let mid = point((p1.x + p2.x) / 2.0, (p1.y + p2.y) / 2.0);
+        assert!(index.within(0, &mid, 0.1).is_empty());
+        assert_eq!(index.within(1, &mid, 0.1).len(), 1);
+        assert!(index.within(5, &mid, 0.1).is_empty());
+        let far = point(mid.x + 1000.0, mid.y + 1000.0);
+        assert!(index.within(1, &far, 1.0).is_empty());
+        let wall = index.nearest_wall(1, &far).expect("no nearest wall");
+        assert_eq!(wall.index(), 0);
+        assert!(index.nearest_wall(0, &far).is_none());
+        //Отрезок поперек стены
+        let (dx, dy) = (p2.y - p1.y, p1.x - p2.x);
+        let a = point(mid.x - dx, mid.y - dy);
+        let b = point(mid.x + dx, mid.y + dy);
+        assert_eq!(index.crossing(1, &a, &b).len(), 1);
+        assert!(index
+            .crossing(1, &far, &point(far.x + 1.0, far.y))
+            .is_empty());
+        //Квадрат вокруг середины стены
+        let square = [
+            point(mid.x - 0.1, mid.y - 0.1),
+            point(mid.x + 0.1, mid.y - 0.1),
+            point(mid.x + 0.1, mid.y + 0.1),
+            point(mid.x - 0.1, mid.y + 0.1),
+        ];
+        assert_eq!(index.intersecting(1, &square).len(), 1);
+        assert!(index.intersecting(1, &[]).is_empty());
+    }
+    #[test]
+    fn spatial_all() {
+        let building = read_test_building(&[rab_e_test_sig_full(1, 3.0)]);
+        let index = SpatialIndex::new(&building);
+        let with_footprint = building
+            .elements()
+            .filter(|e| !e.footprint().points().is_empty())
+            .count();
+        let huge = [
+            point(-1e4, -1e4),
+            point(1e4, -1e4),
+            point(1e4, 1e4),
+            point(-1e4, 1e4),
+        ];
+        assert_eq!(index.intersecting(0, &huge).len(), with_footprint);
+        assert_eq!(index.within(0, &point(0.0, 0.0), 1e5).len(), with_footprint);
+    }
+}
@@ -0,0 +1,470 @@
+//! Выгрузка здания в SQLite
+//!
+//! Таблица storeys и по таблице на каждый тип элементов этажа со ссылкой storey_id.
+//! Поля элементов разворачиваются в колонки (p1_x, sec_rectangle_b), массивы чисел
+//! пишутся JSON-строкой, вложенные массивы объектов (отверстия стен и перегородок,
+//! элементы групп унификации) - в отдельные таблицы со ссылкой на владельца.
+//! Колонки таблиц выводятся из образцов элементов (Building::sample), таблицы
+//! создаются и без строк. id строк и ссылки storey_id, owner_id - с 1, num - номер
+//! элемента в массиве этажа с 0.
+//! Таблица elements - все элементы этажей с материалом, габаритами и геометрией (WKT).
+use crate::sig::building::Building;
+use crate::sig::HasWrite;
+use crate::text::to_value;
+use rusqlite::{params_from_iter, types::Value as SqlValue, Connection};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Ошибка выгрузки
+#[derive(Debug)]
+pub enum Error {
+    Exists(PathBuf), //Файл базы уже есть
+    Sqlite(PathBuf, rusqlite::Error),
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Exists(path) => write!(f, "{} already exists", path.display()),
+            Error::Sqlite(path, why) => write!(f, "couldn't write {}: {}", path.display(), why),
+        }
+    }
+}
+impl std::error::Error for Error {}
+
+/// Таблицы для массивов элементов rab.e: поле, таблица, значение колонки kind
+const GROUPS: [(&str, &str, Option<&str>); 26] = [
+    ("column", "columns", None),
+    ("wall", "walls", None),
+    ("beam", "beams", None),
+    ("slab", "slabs", None),
+    ("load", "loads", None),
+    ("poly", "polys", None),
+    ("node", "nodes", None),
+    ("sig_1", "sig_1", None),
+    ("sig_2", "sig_2", None),
+    ("sig_3", "sig_3", None),
+    ("sig_4", "sig_4", None),
+    ("diagram_force", "diagrams", Some("force")),
+    ("diagram", "diagrams", Some("diagram")),
+    ("f_wall", "founds", None),
+    ("part", "partitions", None),
+    ("sig_5", "sig_5", None),
+    ("lean_on_slab", "leans_on_slab", None),
+    ("diagram_wind_force", "diagrams", Some("wind_force")),
+    ("unification_slab", "unification_slabs", Some("slab")),
+    ("f_slab", "f_slabs", None),
+    ("diagram_unc", "diagrams", Some("unc")),
+    ("unification_found", "unification_founds", None),
+    ("pile", "piles", None),
+    ("unification_wall_slits", "unification_wall_slits", None),
+    ("unification_fslab", "unification_slabs", Some("fslab")),
+    ("f_beam", "f_beams", None),
+];
+
+/// Служебные колонки таблиц вложенных массивов
+const CHILD_COLUMNS: [&str; 5] = ["id", "storey_id", "owner", "owner_id", "num"];
+
+/// Колонки: имя и тип SQLite
+type Columns = Vec<(String, &'static str)>;
+
+/// Таблица: имя, колонки с типами SQLite, строки
+struct Table {
+    name: String,
+    columns: Columns,
+    rows: Vec<Map<String, Value>>,
+}
+impl Table {
+    fn new(name: &str, columns: &[&str]) -> Table {
+        Table {
+            name: name.to_string(),
+            columns: columns
+                .iter()
+                .map(|c| (c.to_string(), column_type(c)))
+                .collect(),
+            rows: vec![],
+        }
+    }
+    /// Добавление колонок, которых ещё нет в таблице
+    fn extend(&mut self, columns: Columns) {
+        for column in columns {
+            if self.columns.iter().all(|(c, _)| *c != column.0) {
+                self.columns.push(column);
+            }
+        }
+    }
+    /// Строка таблицы, возвращает id строки (с 1). Значения без колонки не пишутся
+    fn push(&mut self, row: Map<String, Value>) -> i64 {
+        self.rows.push(row);
+        self.rows.len() as i64
+    }
+    fn write(&self, conn: &Connection) -> rusqlite::Result<()> {
+        let mut columns = vec![];
+        for (column, column_type) in self.columns.iter() {
+            let mut def = format!("\"{}\" {}", column, column_type);
+            if column == "id" {
+                def.push_str(" PRIMARY KEY");
+            } else if column == "storey_id" {
+                def.push_str(" REFERENCES storeys(id)");
+            }
+            columns.push(def);
+        }
+        conn.execute(
+            &format!("CREATE TABLE \"{}\" ({})", self.name, columns.join(", ")),
+            [],
+        )?;
+        let names: Vec<String> = self
+            .columns
+            .iter()
+            .map(|(c, _)| format!("\"{}\"", c))
+            .collect();
+        let marks: Vec<&str> = self.columns.iter().map(|_| "?").collect();
+        let mut insert = conn.prepare(&format!(
+            "INSERT INTO \"{}\" ({}) VALUES ({})",
+            self.name,
+            names.join(", "),
+            marks.join(", ")
+        ))?;
+        for row in self.rows.iter() {
+            let values = self
+                .columns
+                .iter()
+                .map(|(c, _)| sql_value(row.get(c).unwrap_or(&Value::Null)));
+            insert.execute(params_from_iter(values))?;
+        }
+        Ok(())
+    }
+}
+
+/// Тип служебной колонки по имени
+fn column_type(column: &str) -> &'static str {
+    match column {
+        "id" | "storey_id" | "owner_id" | "num" | "etazh_num" | "mat" => "INTEGER",
+        "level" | "height" | "top" | "min_x" | "min_y" | "max_x" | "max_y" => "REAL",
+        _ => "TEXT",
+    }
+}
+
+fn sql_value(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        _ => SqlValue::Text(value.to_string()),
+    }
+}
+
+/// Тип колонки SQLite по значению образца
+fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Bool(_) => "INTEGER",
+        Value::Number(n) if n.is_f64() => "REAL",
+        Value::Number(_) => "INTEGER",
+        _ => "TEXT",
+    }
+}
+
+/// Колонки по образцу элемента: имена как у flatten, типы по значениям образца.
+/// Массивы объектов колонок не дают и возвращаются в nested
+fn sample_columns(sample: &Value, nested: &mut Vec<(String, Vec<Value>)>) -> Columns {
+    let mut row = Map::new();
+    flatten(&mut row, "", sample, nested);
+    row.iter()
+        .map(|(c, v)| (c.clone(), value_type(v)))
+        .collect()
+}
+
+/// Поля элемента в колонки. Массивы объектов возвращаются отдельно: (поле, элементы)
+fn flatten(
+    row: &mut Map<String, Value>,
+    prefix: &str,
+    value: &Value,
+    nested: &mut Vec<(String, Vec<Value>)>,
+) {
+    let map = match value {
+        Value::Object(map) => map,
+        _ => return,
+    };
+    for (key, v) in map.iter() {
+        let name = format!("{}{}", prefix, key.to_lowercase());
+        match v {
+            Value::Object(_) => flatten(row, &format!("{}_", name), v, nested),
+            Value::Array(vec) if !vec.is_empty() && vec.iter().all(Value::is_object) => {
+                nested.push((name, vec.clone()))
+            }
+            _ => {
+                row.insert(name, v.clone());
+            }
+        }
+    }
+}
+
+/// Таблицы здания
+fn tables(building: &Building) -> Vec<Table> {
+    let mut tables = vec![Table::new(
+        "storeys",
+        &[
+            "id",
+            "etazh_num",
+            "name",
+            "signature",
+            "level",
+            "height",
+            "top",
+        ],
+    )];
+    let find = |tables: &mut Vec<Table>, name: &str, columns: &[&str]| -> usize {
+        match tables.iter().position(|t| t.name == name) {
+            Some(n) => n,
+            None => {
+                tables.push(Table::new(name, columns));
+                tables.len() - 1
+            }
+        }
+    };
+    //Все таблицы создаются по образцам элементов, даже если элементов нет
+    let sample = to_value(&Building::sample());
+    let mut groups = vec![];
+    for (field, table, kind) in GROUPS.iter() {
+        let columns: &[&str] = match kind {
+            Some(_) => &["id", "storey_id", "kind", "num"],
+            None => &["id", "storey_id", "num"],
+        };
+        let t = find(&mut tables, table, columns);
+        let mut children: Vec<(String, usize)> = vec![];
+        for element in sample["rab_e"][0][field].as_array().into_iter().flatten() {
+            let mut nested = vec![];
+            tables[t].extend(sample_columns(element, &mut nested));
+            for (key, vec) in nested {
+                let name = match key.as_str() {
+                    "op" => "openings".to_string(),
+                    _ => format!("{}_{}", table, key),
+                };
+                let c = find(&mut tables, &name, &CHILD_COLUMNS);
+                for child in vec.iter() {
+                    tables[c].extend(sample_columns(child, &mut vec![]));
+                }
+                if children.iter().all(|(k, _)| *k != key) {
+                    children.push((key, c));
+                }
+            }
+        }
+        groups.push((t, children));
+    }
+    for storey in building.storeys() {
+        //id строк всех таблиц с 1, как rowid SQLite
+        let storey_id = storey.index + 1;
+        let mut row = Map::new();
+        row.insert("id".to_string(), storey_id.into());
+        row.insert("etazh_num".to_string(), storey.etazh_num.into());
+        row.insert("name".to_string(), storey.title().into());
+        row.insert("signature".to_string(), storey.rab_e.name().into());
+        row.insert("level".to_string(), to_value(&storey.level));
+        row.insert("height".to_string(), to_value(&storey.height));
+        row.insert("top".to_string(), to_value(&storey.top()));
+        tables[0].push(row);
+        let rab_e = to_value(storey.rab_e);
+        for ((field, table, kind), (t, children)) in GROUPS.iter().zip(groups.iter()) {
+            let elements = match rab_e.get(field) {
+                Some(Value::Array(vec)) => vec,
+                _ => continue,
+            };
+            let t = *t;
+            for (num, element) in elements.iter().enumerate() {
+                let mut row = Map::new();
+                row.insert("storey_id".to_string(), storey_id.into());
+                if let Some(kind) = kind {
+                    row.insert("kind".to_string(), (*kind).into());
+                }
+                row.insert("num".to_string(), num.into());
+                let mut nested = vec![];
+                flatten(&mut row, "", element, &mut nested);
+                let id = tables[t].rows.len() as i64 + 1;
+                row.insert("id".to_string(), id.into());
+                tables[t].push(row);
+                for (key, vec) in nested {
+                    let c = match children.iter().find(|(k, _)| *k == key) {
+                        Some((_, c)) => *c,
+                        None => continue,
+                    };
+                    for (num, child) in vec.iter().enumerate() {
+                        let mut row = Map::new();
+                        row.insert("id".to_string(), (tables[c].rows.len() + 1).into());
+                        row.insert("storey_id".to_string(), storey_id.into());
+                        row.insert("owner".to_string(), (*table).into());
+                        row.insert("owner_id".to_string(), id.into());
+                        row.insert("num".to_string(), num.into());
+                        flatten(&mut row, "", child, &mut vec![]);
+                        tables[c].push(row);
+                    }
+                }
+            }
+        }
+    }
+    let mut elements = Table::new(
+        "elements",
+        &[
+            "id",
+            "storey_id",
+            "kind",
+            "num",
+            "mat",
+            "min_x",
+            "min_y",
+            "max_x",
+            "max_y",
+            "footprint",
+        ],
+    );
+    for element in building.elements() {
+        let mut row = Map::new();
+        row.insert("id".to_string(), (elements.rows.len() + 1).into());
+        row.insert("storey_id".to_string(), (element.storey() + 1).into());
+        row.insert("kind".to_string(), element.kind().name().into());
+        row.insert("num".to_string(), element.index().into());
+        row.insert("mat".to_string(), element.mat().into());
+        if let Some(bbox) = element.bbox() {
+            row.insert("min_x".to_string(), to_value(&bbox.min.x));
+            row.insert("min_y".to_string(), to_value(&bbox.min.y));
+            row.insert("max_x".to_string(), to_value(&bbox.max.x));
+            row.insert("max_y".to_string(), to_value(&bbox.max.y));
+        }
+        row.insert(
+            "footprint".to_string(),
+            element.footprint().to_string().into(),
+        );
+        elements.push(row);
+    }
+    tables.push(elements);
+    let mut slits = Table::new("slits", &["id", "num"]);
+    for slit in sample["slits_slt"]["slits"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        slits.extend(sample_columns(slit, &mut vec![]));
+    }
+    let building_value = to_value(building);
+    if let Some(Value::Array(vec)) = building_value["slits_slt"].get("slits") {
+        for (num, slit) in vec.iter().enumerate() {
+            let mut row = Map::new();
+            row.insert("id".to_string(), (num + 1).into());
+            row.insert("num".to_string(), num.into());
+            flatten(&mut row, "", slit, &mut vec![]);
+            slits.push(row);
+        }
+    }
+    tables.push(slits);
+    tables
+}
+
+/// Запись здания в открытую базу
+pub fn export(building: &Building, conn: &mut Connection) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    for table in tables(building).iter() {
+        table.write(&tx)?;
+    }
+    tx.commit()
+}
+
+/// Запись здания в новый файл базы SQLite. Существующий файл не перезаписывается
+pub fn write_sqlite(building: &Building, path: &Path) -> Result<(), Error> {
+    if path.exists() {
+        return Err(Error::Exists(path.to_path_buf()));
+    }
+    let sqlite = |why| Error::Sqlite(path.to_path_buf(), why);
+    let mut conn = Connection::open(path).map_err(sqlite)?;
+    export(building, &mut conn).map_err(sqlite)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::rab_e_sig_test::{
+        rab_e_test_sig, rab_e_test_sig_full, read_test_building, read_test_sig,
+    };
+
+    fn test_conn(sigs: &[Vec<u8>]) -> Connection {
+        let mut conn = Connection::open_in_memory().expect("couldn't open db");
+        export(&read_test_building(sigs), &mut conn).expect("couldn't export");
+        conn
+    }
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |r| r.get(0)).expect("query error")
+    }
+
+    #[test]
+    fn sqlite_full() {
+        let conn = test_conn(&[
+            rab_e_test_sig_full(1, 3.3),
+            rab_e_test_sig(2, 3.0, &[], &[]),
+            read_test_sig("test_sig/slits/2slits.test"),
+        ]);
+        assert_eq!(count(&conn, "SELECT count(*) FROM storeys"), 2);
+        assert_eq!(count(&conn, "SELECT min(id) FROM storeys"), 1);
+        let walls = crate::tests::rab_e_sig_test::test_sig_dir("walls").len() as i64;
+        assert_eq!(count(&conn, "SELECT count(*) FROM walls"), walls);
+        assert_eq!(count(&conn, "SELECT count(*) FROM slits"), 2);
+        assert!(count(&conn, "SELECT count(*) FROM openings WHERE owner = 'walls'") > 0);
+        assert!(count(&conn, "SELECT count(DISTINCT kind) FROM diagrams") == 4);
+        assert_eq!(count(&conn, "SELECT count(DISTINCT kind) FROM elements"), 9);
+        let sql = "SELECT count(*) FROM elements WHERE kind = 'wall' AND max_x >= min_x";
+        assert_eq!(count(&conn, sql), walls);
+        for table in [
+            "columns", "beams", "slabs", "piles", "f_slabs", "f_beams", "polys",
+        ]
+        .iter()
+        {
+            let sql = format!("SELECT count(*) FROM {} WHERE storey_id = 1", table);
+            assert!(count(&conn, &sql) > 0, "{}", table);
+        }
+    }
+    #[test]
+    fn sqlite_query() {
+        let conn = test_conn(&[
+            rab_e_test_sig(1, 3.3, &[], &["test_sig/walls/wall.test"]),
+            rab_e_test_sig(2, 3.0, &[], &["test_sig/walls/wall_2mat.test"]),
+        ]);
+        let sql = "SELECT count(*) FROM walls JOIN storeys ON walls.storey_id = storeys.id \
+                   WHERE storeys.etazh_num > 1 AND walls.b > 0";
+        assert_eq!(count(&conn, sql), 1);
+        let level: f64 = conn
+            .query_row("SELECT level FROM storeys WHERE etazh_num = 2", [], |r| {
+                r.get(0)
+            })
+            .expect("query error");
+        assert!((level - 3.3).abs() < 1e-6);
+    }
+    #[test]
+    fn sqlite_empty() {
+        let conn = test_conn(&[rab_e_test_sig(1, 3.3, &[], &[])]);
+        assert_eq!(count(&conn, "SELECT count(b) FROM walls"), 0);
+        assert_eq!(count(&conn, "SELECT count(x_vec) FROM openings"), 0);
+        assert_eq!(count(&conn, "SELECT count(sec_rectangle_b) FROM beams"), 0);
+        assert_eq!(count(&conn, "SELECT count(p1_x) FROM slits"), 0);
+        let sql = "SELECT type FROM pragma_table_info('walls') WHERE name = 'b'";
+        let column_type: String = conn.query_row(sql, [], |r| r.get(0)).expect("query error");
+        assert_eq!(column_type, "REAL");
+    }
+    #[test]
+    fn sqlite_exists() {
+        let path = std::env::temp_dir().join(format!(
+            "parse_chg_sqlite_exists_{}.sqlite",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let building = Building::default();
+        write_sqlite(&building, &path).expect("couldn't write_sqlite");
+        let size = std::fs::metadata(&path).expect("no file").len();
+        assert!(matches!(
+            write_sqlite(&building, &path),
+            Err(Error::Exists(_))
+        ));
+        assert_eq!(std::fs::metadata(&path).expect("no file").len(), size);
+        std::fs::remove_file(&path).expect("couldn't remove");
+    }
+}
@@ -74,4 +74,238 @@ pub mod rab_e_sig_test {
         };
         original_in
     }
+    /// Сигнатура этажа rab.e, собранная из фрагментов test_sig
+    ///
+    /// Счетчики заголовка заполняются по количеству фрагментов, остальные поля нулевые.
+    pub fn rab_e_test_sig(
+        etazh_num: u16,
+        etazh_h: f32,
+        columns: &[&str],
+        walls: &[&str],
+    ) -> Vec<u8> {
+        rab_e_test_sig_groups(etazh_num, etazh_h, &[columns, walls])
+    }
+    /// Сигнатура этажа rab.e из групп фрагментов
+    ///
+    /// groups[n] - фрагменты n-й группы элементов в порядке счетчиков заголовка
+    /// (0=колонны, 1=стены, 2=балки, 3=плиты, 4=нагрузки, 5=полилинии, 6=узлы...).
+    pub fn rab_e_test_sig_groups(etazh_num: u16, etazh_h: f32, groups: &[&[&str]]) -> Vec<u8> {
+        let mut source: Vec<u8> = vec![];
+        source.extend(&etazh_num.to_le_bytes());
+        source.extend(&etazh_h.to_le_bytes());
+        source.extend(vec![0u8; 4 + 17 + 16 + 4 + 2 + 13]);
+        for n in 0..26 {
+            let num = groups.get(n).map_or(0, |g| g.len()) as u16;
+            source.extend(&num.to_le_bytes());
+        }
+        source.extend(vec![0u8; 180]);
+        for path in groups.iter().flat_map(|g| g.iter()) {
+            source.extend(read_test_sig(path));
+        }
+        let mut out = b"rab.e".to_vec();
+        if etazh_num < 10 {
+            out.extend(&[b'0' + etazh_num as u8, 0u8]);
+        } else {
+            out.extend(etazh_num.to_string().as_bytes());
+        }
+        out.extend(&[0u8; 6]);
+        out.extend(&(source.len() as u64).to_le_bytes());
+        out.extend(source);
+        out
+    }
+    /// Все фрагменты директории test_sig/<dir>
+    pub fn test_sig_dir(dir: &str) -> Vec<String> {
+        let mut paths: Vec<String> = std::path::Path::new("test_sig")
+            .join(dir)
+            .read_dir()
+            .expect("read_dir call failed")
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "test"))
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        paths.sort();
+        paths
+    }
+    /// Этаж со всеми распознанными фрагментами test_sig
+    ///
+    /// Узлы повторены трижды: полилинии и нагрузки из разных файлов ссылаются на узлы
+    /// с номерами до 20, ссылки должны оставаться в пределах вектора (см. RabE::recount).
+    pub fn rab_e_test_sig_full(etazh_num: u16, etazh_h: f32) -> Vec<u8> {
+        let dirs = [
+            "columns",
+            "walls",
+            "beams",
+            "slabs",
+            "loads",
+            "polys",
+            "nodes",
+            "",
+            "",
+            "",
+            "",
+            "diagrams",
+            "diagrams",
+            "founds",
+            "partition",
+            "",
+            "lean_on_slab",
+            "diagrams",
+            "unification_slabs",
+            "f_slabs",
+            "diagrams",
+            "unification_founds",
+            "piles",
+            "unification_wall_slits",
+            "unification_slabs",
+            "f_beams",
+        ];
+        let paths: Vec<Vec<String>> = dirs
+            .iter()
+            .map(|d| {
+                if d.is_empty() {
+                    vec![]
+                } else if *d == "nodes" {
+                    [test_sig_dir(d), test_sig_dir(d), test_sig_dir(d)].concat()
+                } else {
+                    test_sig_dir(d)
+                }
+            })
+            .collect();
+        let paths: Vec<Vec<&str>> = paths
+            .iter()
+            .map(|g| g.iter().map(String::as_str).collect())
+            .collect();
+        let groups: Vec<&[&str]> = paths.iter().map(Vec::as_slice).collect();
+        rab_e_test_sig_groups(etazh_num, etazh_h, &groups)
+    }
+    /// Сигнатура с нерасшифрованным телом: имя в поле 13b, дополненное нулями,
+    /// длина тела u64, тело
+    pub fn raw_test_sig(name: &str, source: &[u8]) -> Vec<u8> {
+        let mut out = name.as_bytes().to_vec();
+        out.resize(13, 0);
+        out.extend(&(source.len() as u64).to_le_bytes());
+        out.extend(source);
+        out
+    }
+    /// Файл *.chg (BUILDER012) из готовых сигнатур
+    pub fn building_test_sig(sigs: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = b"BUILDER012".to_vec();
+        for sig in sigs {
+            out.extend(sig);
+        }
+        out
+    }
+    /// Здание, прочитанное из файла BUILDER012 с сигнатурами sigs
+    pub fn read_test_building(sigs: &[Vec<u8>]) -> crate::sig::building::Building {
+        let (_, building) = crate::sig::building::read_original(&building_test_sig(sigs))
+            .expect("couldn't read_original");
+        building
+    }
+}
+
+#[cfg(test)]
+mod json_tests {
+    use crate::sig::building::read_original;
+    use crate::sig::building::Building;
+    use crate::sig::HasWrite;
+    use crate::tests::rab_e_sig_test::{
+        building_test_sig, rab_e_test_sig, rab_e_test_sig_full, read_test_sig,
+    };
+
+    #[test]
+    fn building_to_json() {
+        let original_in = building_test_sig(&[rab_e_test_sig(
+            1,
+            3.0,
+            &["test_sig/columns/column_circle.test"],
+            &["test_sig/walls/wall.test"],
+        )]);
+        let (_, building) = read_original(&original_in).expect("couldn't read_original");
+        let json = serde_json::to_value(&building).expect("couldn't serialize");
+        assert_eq!(json["file_type"], "BUILDER012");
+        let rab_e = &json["rab_e"][0];
+        assert_eq!(rab_e["head"]["etazh_num"], 1);
+        assert!(rab_e["column"][0]["sec"]["Circle"]["d"].is_number());
+        assert!(rab_e["wall"][0]["b"].is_number());
+        let ws = rab_e["wall"][0]["ws"].as_str().expect("ws not hex");
+        assert_eq!(ws.len(), 17 * 2);
+        assert!(json["slits_slt"].is_null());
+    }
+    #[test]
+    fn json_read_write() {
+        let original_in = building_test_sig(&[
+            rab_e_test_sig_full(1, 3.3),
+            rab_e_test_sig_full(2, 3.0),
+            read_test_sig("test_sig/slits/big_slits_R.test"),
+        ]);
+        let (_, building) = read_original(&original_in).expect("couldn't read_original");
+        let json = serde_json::to_string(&building).expect("couldn't serialize");
+        let building: Building = serde_json::from_str(&json).expect("couldn't deserialize");
+        assert_eq!(original_in, building.write());
+    }
+    #[test]
+    fn json_non_finite() {
+        let original_in = building_test_sig(&[rab_e_test_sig(1, 3.0, &[], &[])]);
+        let (_, mut building) = read_original(&original_in).expect("couldn't read_original");
+        building.rab_e[0].head.etazh_h = f32::NAN;
+        let original_in = building.write();
+        let json = crate::json::to_string(&building).expect("couldn't serialize");
+        let value = serde_json::from_str(&json).expect("not json");
+        let building: Building = crate::json::from_value(value).expect("couldn't deserialize");
+        assert_eq!(original_in, building.write());
+        let text = crate::text::to_text(&building);
+        let building = crate::text::from_text(&text).expect("couldn't read text");
+        assert_eq!(original_in, building.write());
+    }
+    #[test]
+    fn json_recount() {
+        let walls = [
+            "test_sig/walls/wall.test",
+            "test_sig/walls/wall_opening_2.test",
+        ];
+        let original_in = building_test_sig(&[rab_e_test_sig(1, 3.0, &[], &walls)]);
+        let (_, building) = read_original(&original_in).expect("couldn't read_original");
+        let openings = building.rab_e[0].wall[1].get_openings().len();
+        assert!(openings > 0);
+        let mut json = serde_json::to_value(&building).expect("couldn't serialize");
+        //Удаление стены и отверстия
+        json["rab_e"][0]["wall"]
+            .as_array_mut()
+            .expect("no walls")
+            .remove(0);
+        let mut bad = json.clone();
+        json["rab_e"][0]["wall"][0]["op"]
+            .as_array_mut()
+            .expect("no openings")
+            .remove(0);
+        let mut building: Building = serde_json::from_value(json).expect("couldn't deserialize");
+        building.recount().expect("couldn't recount");
+        let (_, rebuilt) = read_original(&building.write()).expect("couldn't read_original");
+        assert_eq!(rebuilt.rab_e[0].wall.len(), 1);
+        assert_eq!(rebuilt.rab_e[0].wall[0].get_openings().len(), openings - 1);
+        //Координаты отверстия не парами
+        bad["rab_e"][0]["wall"][0]["op"][0]["x_vec"]
+            .as_array_mut()
+            .expect("no x_vec")
+            .pop();
+        let mut building: Building = serde_json::from_value(bad).expect("couldn't deserialize");
+        assert!(building.recount().is_err());
+    }
+    #[test]
+    fn json_recount_ranges() {
+        let original_in = building_test_sig(&[rab_e_test_sig_full(1, 3.0)]);
+        let (_, building) = read_original(&original_in).expect("couldn't read_original");
+        let mut json = serde_json::to_value(&building).expect("couldn't serialize");
+        //Полилинии ссылаются на удаленные узлы
+        json["rab_e"][0]["node"]
+            .as_array_mut()
+            .expect("no nodes")
+            .truncate(2);
+        let mut building: Building = serde_json::from_value(json).expect("couldn't deserialize");
+        let why = building
+            .recount()
+            .expect_err("recount with broken node refs");
+        assert!(why.contains("out of 2"), "{}", why);
+    }
 }
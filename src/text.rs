@@ -0,0 +1,247 @@
+//! Текстовый формат *.chg
+//!
+//! Построчная запись здания для просмотра изменений в git (textconv). Каждая строка:
+//! путь к значению и значение в JSON. Элементы этажей и разрезы - по одному на строку,
+//! нераспознанные байты - hex-строкой, в том числе хвост файла после всех сигнатур
+//! (строка `leftover`). Строки с `#` - комментарии.
+//!
+//! ```text
+//! file_type "BUILDER012"
+//! barpbres_fe null
+//! # Этаж 1 (+0.000)..+3.300, h = 3.3
+//! rab_e[0] head {"etazh_num":1,"etazh_h":3.3,...}
+//! rab_e[0] wall[17] {"p1":{"x":0.0,"y":0.0},...,"b":20.0,...}
+//! rab_e[0] beam []
+//! slits_slt slits[0] {"name":"31000000...",...}
+//! ```
+use crate::sig::building::Building;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+const HEAD: &str = "# parse_chg text";
+
+/// Значение в JSON-дерево
+///
+/// Через строку, чтобы f32 остались в коротком виде (3.3, а не 3.299999952316284).
+/// Неконечные f32 - строкой (см. json).
+pub fn to_value<T: Serialize>(value: &T) -> Value {
+    match crate::json::to_string(value).map(|s| serde_json::from_str(&s)) {
+        Ok(Ok(value)) => value,
+        _ => panic!("couldn't serialize value"),
+    }
+}
+
+/// Здание в текстовый формат
+pub fn to_text(building: &Building) -> String {
+    let value = match to_value(building) {
+        Value::Object(value) => value,
+        _ => panic!("couldn't serialize building"),
+    };
+    let storeys = building.storeys();
+    let mut out = String::new();
+    out.push_str(HEAD);
+    out.push('\n');
+    for (key, v) in value.iter() {
+        match v {
+            Value::Array(vec) if key == "rab_e" && !vec.is_empty() => {
+                for (count, rab_e) in vec.iter().enumerate() {
+                    let storey = storeys
+                        .iter()
+                        .find(|s| std::ptr::eq(s.rab_e, &building.rab_e[count]));
+                    if let Some(storey) = storey {
+                        out.push_str(&format!("# {}\n", storey));
+                    }
+                    write_object(&mut out, &format!("{}[{}]", key, count), rab_e);
+                }
+            }
+            Value::Object(_) => write_object(&mut out, key, v),
+            _ => write_line(&mut out, key, v),
+        }
+    }
+    out
+}
+
+fn write_object(out: &mut String, prefix: &str, value: &Value) {
+    let map = match value {
+        Value::Object(map) => map,
+        _ => return write_line(out, prefix, value),
+    };
+    for (key, v) in map.iter() {
+        match v {
+            Value::Array(vec) if !vec.is_empty() && vec.iter().all(Value::is_object) => {
+                for (count, element) in vec.iter().enumerate() {
+                    write_line(out, &format!("{} {}[{}]", prefix, key, count), element);
+                }
+            }
+            _ => write_line(out, &format!("{} {}", prefix, key), v),
+        }
+    }
+}
+
+fn write_line(out: &mut String, path: &str, value: &Value) {
+    out.push_str(path);
+    out.push(' ');
+    out.push_str(&value.to_string());
+    out.push('\n');
+}
+
+/// Здание из текстового формата
+pub fn from_text(text: &str) -> Result<Building, String> {
+    let mut root = Map::new();
+    for (count, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        read_line(&mut root, line).map_err(|why| format!("line {}: {}", count + 1, why))?;
+    }
+    let mut building: Building =
+        crate::json::from_value(Value::Object(root)).map_err(|why| why.to_string())?;
+    building.recount()?;
+    Ok(building)
+}
+
+fn read_line(root: &mut Map<String, Value>, line: &str) -> Result<(), String> {
+    let mut path = vec![];
+    let mut rest = line;
+    while path.len() < 2 {
+        let (token, tail) = match rest.find(' ') {
+            None => break,
+            Some(n) => (&rest[..n], &rest[n + 1..]),
+        };
+        match read_key(token) {
+            None => break,
+            Some(key) => path.push(key),
+        }
+        rest = tail;
+    }
+    let value: Value = serde_json::from_str(rest).map_err(|why| why.to_string())?;
+    match path.as_slice() {
+        [key] => set(root, key, value),
+        [(name, index), key] => match slot(root, name, *index)? {
+            Value::Object(map) => set(map, key, value),
+            _ => Err(format!("{} is not an object", name)),
+        },
+        _ => Err("no path or value".to_string()),
+    }
+}
+
+/// Ключ пути: `name` или `name[n]`
+fn read_key(token: &str) -> Option<(String, Option<usize>)> {
+    if token == "null" || token == "true" || token == "false" {
+        return None;
+    }
+    let (name, index) = match token.find('[') {
+        None => (token, None),
+        Some(n) => {
+            let index = token[n + 1..].strip_suffix(']')?.parse().ok()?;
+            (&token[..n], Some(index))
+        }
+    };
+    let ident = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if ident {
+        Some((name.to_string(), index))
+    } else {
+        None
+    }
+}
+
+/// Значение по ключу, для name[n] - n-й элемент массива. Отсутствующее создается объектом
+fn slot<'a>(
+    map: &'a mut Map<String, Value>,
+    name: &str,
+    index: Option<usize>,
+) -> Result<&'a mut Value, String> {
+    let entry = match index {
+        None => map
+            .entry(name.to_string())
+            .or_insert_with(|| Value::Object(Map::new())),
+        Some(_) => map
+            .entry(name.to_string())
+            .or_insert_with(|| Value::Array(vec![])),
+    };
+    match (index, entry) {
+        (None, entry) => Ok(entry),
+        (Some(n), Value::Array(vec)) => {
+            if vec.len() <= n {
+                vec.resize(n + 1, Value::Object(Map::new()));
+            }
+            Ok(&mut vec[n])
+        }
+        (Some(_), _) => Err(format!("{} is not an array", name)),
+    }
+}
+
+fn set(
+    map: &mut Map<String, Value>,
+    key: &(String, Option<usize>),
+    value: Value,
+) -> Result<(), String> {
+    let (name, index) = key;
+    match index {
+        None => {
+            map.insert(name.clone(), value);
+        }
+        Some(_) => *slot(map, name, *index)? = value,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sig::building::read_original;
+    use crate::sig::HasWrite;
+    use crate::tests::rab_e_sig_test::{
+        building_test_sig, rab_e_test_sig, rab_e_test_sig_full, read_test_sig,
+    };
+
+    #[test]
+    fn text_read_write() {
+        let original_in = building_test_sig(&[
+            rab_e_test_sig_full(1, 3.3),
+            rab_e_test_sig(2, 3.0, &[], &[]),
+            read_test_sig("test_sig/slits/2slits.test"),
+        ]);
+        let (_, building) = read_original(&original_in).expect("couldn't read_original");
+        let text = to_text(&building);
+        let building = from_text(&text).expect("couldn't read text");
+        assert_eq!(original_in, building.write());
+    }
+    #[test]
+    fn text_lines() {
+        let original_in = building_test_sig(&[
+            rab_e_test_sig(1, 3.0, &[], &["test_sig/walls/wall.test"]),
+            read_test_sig("test_sig/slits/2slits.test"),
+        ]);
+        let (_, building) = read_original(&original_in).expect("couldn't read_original");
+        let text = to_text(&building);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], HEAD);
+        assert_eq!(lines[1], "file_type \"BUILDER012\"");
+        assert!(lines.contains(&"barpbres_fe null"));
+        assert!(lines
+            .iter()
+            .any(|l| l.starts_with("rab_e[0] wall[0] {\"p1\":")));
+        assert!(lines.contains(&"rab_e[0] beam []"));
+        assert!(lines.contains(&"slits_slt slits_num 2"));
+        assert!(lines.iter().any(|l| l.starts_with("slits_slt slits[1] {")));
+    }
+    #[test]
+    fn text_leftover() {
+        let mut original_in = building_test_sig(&[rab_e_test_sig(1, 3.0, &[], &[])]);
+        let (_, mut building) = read_original(&original_in).expect("couldn't read_original");
+        building.leftover = vec![0xde, 0xad];
+        original_in.extend(&building.leftover);
+        let text = to_text(&building);
+        assert!(text.lines().any(|l| l == "leftover \"dead\""));
+        let building = from_text(&text).expect("couldn't read text");
+        assert_eq!(original_in, building.write());
+    }
+    #[test]
+    fn text_bad_line() {
+        assert!(from_text("file_type").is_err());
+        assert!(from_text("rab_e[x] head {}").is_err());
+    }
+}